@@ -3,7 +3,7 @@ import { Construct } from 'constructs';
 import * as events from "aws-cdk-lib/aws-events";
 import * as lambda from "aws-cdk-lib/aws-lambda";
 import * as targets from "aws-cdk-lib/aws-events-targets";
-import { Schedule, ScheduleExpression } from "@aws-cdk/aws-scheduler-alpha";
+import { Schedule, ScheduleExpression, ScheduleTargetInput } from "@aws-cdk/aws-scheduler-alpha";
 import { LambdaInvoke } from "@aws-cdk/aws-scheduler-targets-alpha";
 import { join } from 'path';
 import { AttributeType, BillingMode, Table } from 'aws-cdk-lib/aws-dynamodb';
@@ -28,6 +28,20 @@ export class LeaguePointService extends cdk.Stack {
                 type: AttributeType.STRING,
             },
             billingMode: BillingMode.PAY_PER_REQUEST,
+            // Set on the snapshots older than the retention policy allows
+            timeToLiveAttribute: "expiresAt",
+        });
+        // The snapshots of every player by game and UTC day, for the server-wide daily views
+        table.addGlobalSecondaryIndex({
+            indexName: "byDay",
+            partitionKey: {
+                name: "day",
+                type: AttributeType.STRING,
+            },
+            sortKey: {
+                name: "sk",
+                type: AttributeType.STRING,
+            },
         });
         const lpHandler = new lambda.Function(
             this,
@@ -74,6 +88,18 @@ export class LeaguePointService extends cdk.Stack {
             target: new LambdaInvoke(lpHandler, {}),
             description: 'This is a schedule to pull the League Points of users every day before midnight',
         });
+        new Schedule(this, `${prefix}-retention-schedule`, {
+            schedule: ScheduleExpression.cron({
+                minute: '30',
+                hour: '3',
+                weekDay: 'SUN',
+                timeZone: TimeZone.AMERICA_NEW_YORK,
+            }),
+            target: new LambdaInvoke(lpHandler, {
+                input: ScheduleTargetInput.fromObject({ retention: true }),
+            }),
+            description: 'This is a schedule to downsample the old League Points snapshots every week',
+        });
     }
 }
 
@@ -0,0 +1,87 @@
+use super::winrate::WinRateError;
+use crate::discord::{DiscordPayload, DiscordResponse, InteractionResponse, ResponseType};
+use crate::error::Result;
+use crate::AppState;
+use chrono::Utc;
+use lp_db::builders::audit::{AuditAction, AuditEntry};
+
+/// Most entries shown
+const LIMIT: usize = 20;
+const DEFAULT_DAYS: i64 = 7;
+
+pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordResponse> {
+    let data = body.data.as_ref().ok_or(WinRateError::MissingData)?;
+    let guild_id = body.guild_id.as_deref().ok_or(WinRateError::MissingGuild)?;
+    let days = data
+        .options
+        .iter()
+        .flatten()
+        .find(|o| o.name == "days")
+        .and_then(|o| o.value.as_ref().and_then(|v| v.as_i64()))
+        .unwrap_or(DEFAULT_DAYS)
+        .max(1);
+
+    let since = Utc::now().timestamp() - days * 24 * 60 * 60;
+    let entries = state
+        .lp_db_client
+        .audit()
+        .list(guild_id, since..)
+        .newest_first()
+        .limit(LIMIT)
+        .send()
+        .await?;
+
+    let mut banner = format!("** --- Changes of the last {} days --- **\n\n", days);
+    if entries.is_empty() {
+        banner.push_str("No changes");
+    }
+    banner.push_str(&audit_lines(&entries, state).await?);
+
+    let res = InteractionResponse::new(ResponseType::ChannelMessageWithSource, banner);
+    Ok(res)
+}
+
+/// One line per entry, with the current Riot ID of the players
+async fn audit_lines(entries: &[AuditEntry], state: &AppState) -> Result<String> {
+    let puuids = entries
+        .iter()
+        .filter(|entry| entry.action != AuditAction::Config)
+        .map(|entry| entry.target.clone())
+        .collect::<Vec<_>>();
+    let profiles = state
+        .lp_db_client
+        .profiles()
+        .get_many()
+        .puuids(&puuids)
+        .send()
+        .await?;
+
+    let mut lines = String::new();
+    for entry in entries {
+        let actor = entry
+            .actor
+            .as_ref()
+            .map_or("The bot".to_string(), |actor| format!("<@{}>", actor));
+        let player = || {
+            profiles
+                .iter()
+                .find(|p| p.puuid() == entry.target)
+                .map_or("an unknown player", |p| p.riot_id.as_str())
+        };
+        let game = match entry.game_type {
+            Some(lp_db::GameType::League) => " (League)",
+            Some(lp_db::GameType::Tft) => " (TFT)",
+            None => "",
+        };
+        let change = match entry.action {
+            AuditAction::Track => format!("tracked **{}**{}", player(), game),
+            AuditAction::Untrack => format!("untracked **{}**{}", player(), game),
+            AuditAction::Config => match &entry.detail {
+                Some(value) => format!("set **{}** to {}", entry.target, value),
+                None => format!("changed **{}**", entry.target),
+            },
+        };
+        lines.push_str(&format!("<t:{}:R> {} {}\n", entry.timestamp, actor, change));
+    }
+    Ok(lines)
+}
@@ -4,10 +4,12 @@ use crate::discord::{
 };
 use crate::error::Result;
 use crate::AppState;
+use lp_db::builders::tracking::TrackingItem;
 use std::str::FromStr;
 
 pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordResponse> {
     let data = body.data.as_ref().ok_or(WinRateError::MissingData)?;
+    let guild_id = body.guild_id.as_deref().ok_or(WinRateError::MissingGuild)?;
     let option = data.options.as_ref().ok_or(WinRateError::MissingOptions)?;
     let game_type = option
         .iter()
@@ -24,14 +26,12 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
                 .lp_db_client
                 .tracking(lp_db::GameType::Tft)
                 .list()
+                .guild_id(guild_id)
                 .send()
                 .await?;
 
             let mut banner = String::from("** --- TFT --- **\n\nTracked Riot IDs:\n");
-
-            for (i, user) in tracked_users.iter().enumerate() {
-                banner.push_str(&format!("{}.\t{}\n", (i + 1), user.summoner_name));
-            }
+            banner.push_str(&tracked_lines(&tracked_users, state).await?);
 
             let res = InteractionResponse::new(ResponseType::ChannelMessageWithSource, banner);
             Ok(res)
@@ -41,17 +41,42 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
                 .lp_db_client
                 .tracking(lp_db::GameType::League)
                 .list()
+                .guild_id(guild_id)
                 .send()
                 .await?;
 
             let mut banner = String::from("** --- League --- **\n\nTracked Riot IDs:\n");
-
-            for (i, user) in tracked_users.iter().enumerate() {
-                banner.push_str(&format!("{}.\t{}\n", (i + 1), user.summoner_name));
-            }
+            banner.push_str(&tracked_lines(&tracked_users, state).await?);
 
             let res = InteractionResponse::new(ResponseType::ChannelMessageWithSource, banner);
             Ok(res)
         }
     }
 }
+
+/// One line per tracked user, with the current Riot ID of their profile and the previous one
+async fn tracked_lines(tracked_users: &[TrackingItem], state: &AppState) -> Result<String> {
+    let puuids = tracked_users
+        .iter()
+        .map(|user| user.puuid.clone())
+        .collect::<Vec<_>>();
+    let profiles = state
+        .lp_db_client
+        .profiles()
+        .get_many()
+        .puuids(&puuids)
+        .send()
+        .await?;
+
+    let mut lines = String::new();
+    for (i, user) in tracked_users.iter().enumerate() {
+        let profile = profiles.iter().find(|p| p.puuid() == user.puuid);
+        let riot_id = profile.map_or(user.summoner_name.as_str(), |p| p.riot_id.as_str());
+        lines.push_str(&format!("{}.\t{}", (i + 1), riot_id));
+        if let Some(previous) = profile.and_then(|p| p.previous_riot_id()) {
+            lines.push_str(&format!(" *(formerly {})*", previous));
+        }
+        lines.push('\n');
+    }
+    Ok(lines)
+}
@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod list;
 pub mod ping;
 pub mod recap;
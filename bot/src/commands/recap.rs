@@ -7,9 +7,8 @@ use crate::error::Result;
 use crate::AppState;
 use chrono::{TimeZone, Utc};
 use chrono_tz::US::Eastern;
-use lp_db::GameType as DbGameType;
+use lp_db::{GameType as DbGameType, Queue as DbQueue};
 use riot_sdk::account::AccountRegion;
-use riot_sdk::league::summoner::league::league_type_str;
 use riot_sdk::matches::Region as MatchesRegion;
 use riot_sdk::summoner::Region as SummonerRegion;
 use riot_sdk::{PlayerRank, Queue};
@@ -45,13 +44,18 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
         .iter()
         .find(|o| o.name == "yesterday")
         .and_then(|o| o.value.as_ref().map(|v| v.as_bool().unwrap_or(false)));
+    let queue = option
+        .iter()
+        .find(|o| o.name == "queue")
+        .and_then(|o| o.value.as_ref().and_then(|v| v.as_str()));
 
     let tag_line = tag_line.as_str().unwrap();
     let game_name = game_name.as_str().unwrap();
     let game_type = GameType::from_str(game_type.as_str().unwrap())?;
+    let queue = game_type.queue(queue)?;
 
     let riot_id_data = state
-        .account_client
+        .account_client(&game_type)
         .account(AccountRegion::AMERICAS)
         .get_by_riot_id(game_name, tag_line)
         .send()
@@ -60,14 +64,17 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
     let riot_id = format!("{}#{}", riot_id_data.game_name, riot_id_data.tag_line);
 
     match game_type {
-        GameType::League => run_league(&riot_id, &riot_id_data.puuid, yesterday, state).await,
-        GameType::Tft => run_tft(&riot_id, &riot_id_data.puuid, yesterday, state).await,
+        GameType::League => {
+            run_league(&riot_id, &riot_id_data.puuid, queue, yesterday, state).await
+        }
+        GameType::Tft => run_tft(&riot_id, &riot_id_data.puuid, queue, yesterday, state).await,
     }
 }
 
 async fn run_league(
     riot_id: &str,
     puuid: &str,
+    queue: DbQueue,
     yesterday: Option<bool>,
     state: &AppState,
 ) -> Result<DiscordResponse> {
@@ -83,7 +90,10 @@ async fn run_league(
         .send()
         .await?;
 
-    let queue_type = Queue::RankedSolo5x5;
+    let queue_type = match queue {
+        DbQueue::Flex => Queue::RankedFlex5x5,
+        _ => Queue::RankedSolo5x5,
+    };
 
     let start_time = date
         .timezone()
@@ -111,26 +121,34 @@ async fn run_league(
         .summoner(SummonerRegion::NA1)
         .get_league_details(&summoner_data.id)
         .send();
-    let daily_lp = state
-        .lp_db_client
-        .league_points(DbGameType::League)
-        .get_between()
-        .id(&summoner_data.id)
-        .start_time(start_time)
-        .end_time(end_time)
+    let league_points = state.lp_db_client.league_points(DbGameType::League);
+    let morning_lp = league_points
+        .first_of_day(&summoner_data.puuid, date.date_naive(), &WORKING_TZ)
+        .queue(queue)
+        // League points are not comparable across a ranked reset
+        .within_split_of(end_time)
+        .send();
+    let evening_lp = league_points
+        .at_or_before(&summoner_data.puuid, end_time)
+        .queue(queue)
+        .within_split_of(end_time)
         .send();
 
-    let (game_ids, league_details, daily_lp) = futures::join!(game_ids, league_details, daily_lp);
+    let (game_ids, league_details, morning_lp, evening_lp) =
+        futures::join!(game_ids, league_details, morning_lp, evening_lp);
 
     let game_ids = game_ids?;
     let league_details = league_details?;
-    let daily_lp = daily_lp?;
+    let morning_lp = morning_lp?;
+    let evening_lp = evening_lp?;
 
     let league_details = league_details
         .iter()
-        .find(|l| l.queue_type == league_type_str(&queue_type));
+        .find(|l| l.queue_type == queue.riot_queue_type());
 
     let game_details = get_league_details_from_cache(&game_ids, state).await?;
+    let incomplete_warning = game_details.incomplete_warning().unwrap_or_default();
+    let game_details = game_details.games;
 
     let user_games = game_details.iter().map(|game| {
         game.info
@@ -168,78 +186,79 @@ async fn run_league(
         None => "Unranked".to_string(),
     };
     let mut banner = format!(
-        "** --- League ---**\n\n**{}** {}\n\nRecap for **{}**\n\n{}",
+        "** --- League ---**\n\n**{}** {}\n\n[{}] Recap for **{}**\n\n{}",
         riot_id,
         league_banner,
+        queue_name(queue),
         date.format("%A, %B %e, %Y"),
         winrate_line,
     );
+    banner.push_str(&incomplete_warning);
 
     if let Some(league_details) = league_details {
-        if let Some(daily_lp) = daily_lp {
-            let mut daily_lp_iter = daily_lp.iter();
-            let morning_lp_snapshot = daily_lp_iter.next();
-            if let Some(morning_lp_snapshot) = morning_lp_snapshot {
-                let evening_lp_snapshot = daily_lp_iter.next();
-
-                let start_rank = PlayerRank::parse_str(
-                    &morning_lp_snapshot.tier,
-                    &morning_lp_snapshot.rank,
-                    morning_lp_snapshot.league_points,
-                );
+        if let Some(morning_lp_snapshot) = &morning_lp {
+            // The last snapshot of the day when it is not the first one
+            let evening_lp_snapshot = evening_lp
+                .as_ref()
+                .filter(|s| s.timestamp() > morning_lp_snapshot.timestamp());
+
+            let start_rank = PlayerRank::parse_str(
+                &morning_lp_snapshot.tier,
+                &morning_lp_snapshot.rank,
+                morning_lp_snapshot.league_points,
+            );
 
-                match evening_lp_snapshot {
-                    Some(evening_lp_snapshot) => {
-                        let current_rank = match yesterday {
-                            Some(true) => PlayerRank::parse_str(
-                                &evening_lp_snapshot.tier,
-                                &evening_lp_snapshot.rank,
-                                evening_lp_snapshot.league_points,
-                            ),
-                            _ => PlayerRank::parse_str(
-                                league_details.tier.as_ref().unwrap(),
-                                league_details.rank.as_ref().unwrap(),
-                                league_details.league_points as i32,
-                            ),
-                        };
-
-                        let rank_change = start_rank.points_difference(&current_rank);
-
-                        let rank_change_str = match rank_change.cmp(&0) {
-                            Ordering::Greater => format!("**+{}**", rank_change),
-                            Ordering::Less => format!("**{}**", rank_change),
-                            Ordering::Equal => format!("**{}**", rank_change),
-                        };
-                        banner.push_str(&format!(
-                            "\n\n`LP DAILY RECAP`\n\nstart\t{}\nend\t  {}\nGain {}",
-                            start_rank.formatted_rank(),
-                            current_rank.formatted_rank(),
-                            rank_change_str
-                        ));
-                    }
-                    None => {
-                        // banner.push_str("\n\n*No evening info, LP info will be available tomorrow*")
-                        let current_rank = PlayerRank::parse_str(
+            match evening_lp_snapshot {
+                Some(evening_lp_snapshot) => {
+                    let current_rank = match yesterday {
+                        Some(true) => PlayerRank::parse_str(
+                            &evening_lp_snapshot.tier,
+                            &evening_lp_snapshot.rank,
+                            evening_lp_snapshot.league_points,
+                        ),
+                        _ => PlayerRank::parse_str(
                             league_details.tier.as_ref().unwrap(),
                             league_details.rank.as_ref().unwrap(),
                             league_details.league_points as i32,
-                        );
-
-                        let rank_change = start_rank.points_difference(&current_rank);
-
-                        let rank_change_str = match rank_change.cmp(&0) {
-                            Ordering::Greater => format!("**+{}**", rank_change),
-                            Ordering::Less => format!("**{}**", rank_change),
-                            Ordering::Equal => format!("**{}**", rank_change),
-                        };
-                        banner.push_str(&format!(
-                            "\n\n`LP RECAP as of {}`\n\nstart\t{}\nend\t  {}\nGain {}",
-                            date.format("%A, %B %e, %Y %H:%M:%S"),
-                            start_rank.formatted_rank(),
-                            current_rank.formatted_rank(),
-                            rank_change_str
-                        ));
-                    }
+                        ),
+                    };
+
+                    let rank_change = start_rank.points_difference(&current_rank);
+
+                    let rank_change_str = match rank_change.cmp(&0) {
+                        Ordering::Greater => format!("**+{}**", rank_change),
+                        Ordering::Less => format!("**{}**", rank_change),
+                        Ordering::Equal => format!("**{}**", rank_change),
+                    };
+                    banner.push_str(&format!(
+                        "\n\n`LP DAILY RECAP`\n\nstart\t{}\nend\t  {}\nGain {}",
+                        start_rank.formatted_rank(),
+                        current_rank.formatted_rank(),
+                        rank_change_str
+                    ));
+                }
+                None => {
+                    // banner.push_str("\n\n*No evening info, LP info will be available tomorrow*")
+                    let current_rank = PlayerRank::parse_str(
+                        league_details.tier.as_ref().unwrap(),
+                        league_details.rank.as_ref().unwrap(),
+                        league_details.league_points as i32,
+                    );
+
+                    let rank_change = start_rank.points_difference(&current_rank);
+
+                    let rank_change_str = match rank_change.cmp(&0) {
+                        Ordering::Greater => format!("**+{}**", rank_change),
+                        Ordering::Less => format!("**{}**", rank_change),
+                        Ordering::Equal => format!("**{}**", rank_change),
+                    };
+                    banner.push_str(&format!(
+                        "\n\n`LP RECAP as of {}`\n\nstart\t{}\nend\t  {}\nGain {}",
+                        date.format("%A, %B %e, %Y %H:%M:%S"),
+                        start_rank.formatted_rank(),
+                        current_rank.formatted_rank(),
+                        rank_change_str
+                    ));
                 }
             }
         }
@@ -260,6 +279,7 @@ async fn run_league(
 async fn run_tft(
     riot_id: &str,
     puuid: &str,
+    queue: DbQueue,
     yesterday: Option<bool>,
     state: &AppState,
 ) -> Result<DiscordResponse> {
@@ -269,14 +289,12 @@ async fn run_tft(
         _ => now,
     };
     let summoner_data = state
-        .league_client
+        .tft_client
         .summoner(SummonerRegion::NA1)
         .get_by_puuid(puuid)
         .send()
         .await?;
 
-    let queue_type = Queue::TFTRanked;
-
     let start_time = date
         .timezone()
         .from_local_datetime(&date.date_naive().and_hms_opt(0, 0, 0).unwrap())
@@ -302,26 +320,34 @@ async fn run_tft(
         .summoner(SummonerRegion::NA1)
         .get_league_details(&summoner_data.id)
         .send();
-    let daily_lp = state
-        .lp_db_client
-        .league_points(DbGameType::Tft)
-        .get_between()
-        .id(&summoner_data.id)
-        .start_time(start_time)
-        .end_time(end_time)
+    let league_points = state.lp_db_client.league_points(DbGameType::Tft);
+    let morning_lp = league_points
+        .first_of_day(&summoner_data.puuid, date.date_naive(), &WORKING_TZ)
+        .queue(queue)
+        // League points are not comparable across a ranked reset
+        .within_split_of(end_time)
+        .send();
+    let evening_lp = league_points
+        .at_or_before(&summoner_data.puuid, end_time)
+        .queue(queue)
+        .within_split_of(end_time)
         .send();
 
-    let (game_ids, league_details, daily_lp) = futures::join!(game_ids, league_details, daily_lp);
+    let (game_ids, league_details, morning_lp, evening_lp) =
+        futures::join!(game_ids, league_details, morning_lp, evening_lp);
 
     let game_ids = game_ids?;
     let league_details = league_details?;
-    let daily_lp = daily_lp?;
+    let morning_lp = morning_lp?;
+    let evening_lp = evening_lp?;
 
     let league_details = league_details
         .iter()
-        .find(|l| l.queue_type == league_type_str(&queue_type));
+        .find(|l| l.queue_type == queue.riot_queue_type());
 
     let game_details = get_tft_details_from_cache(&game_ids, state).await?;
+    let incomplete_warning = game_details.incomplete_warning().unwrap_or_default();
+    let game_details = game_details.games;
 
     let user_games = game_details.iter().map(|game| {
         game.info
@@ -344,6 +370,10 @@ async fn run_tft(
         false => format!("{}/{} **{:.2}%** winrate", won_games, lost_games, winrate),
     };
     let league_banner = match league_details {
+        Some(league) if queue == DbQueue::HyperRoll => hyper_roll_rank(
+            league.rated_tier.as_deref().unwrap_or_default(),
+            league.rated_rating.unwrap_or_default(),
+        ),
         Some(league) => {
             let rank = PlayerRank::parse_str(
                 league.tier.as_ref().unwrap(),
@@ -357,31 +387,58 @@ async fn run_tft(
         None => "Unranked".to_string(),
     };
     let mut banner = format!(
-        "** --- TFT --- **\n\n**{}** {}\n\nRecap for **{}**\n\n{}",
+        "** --- TFT --- **\n\n**{}** {}\n\n[{}] Recap for **{}**\n\n{}",
         riot_id,
         league_banner,
+        queue_name(queue),
         date.format("%A, %B %e, %Y"),
         winrate_line,
     );
+    banner.push_str(&incomplete_warning);
 
     if let Some(league_details) = league_details {
-        if let Some(daily_lp) = daily_lp {
-            let mut daily_lp_iter = daily_lp.iter();
-            let morning_lp_snapshot = daily_lp_iter.next();
-            if let Some(morning_lp_snapshot) = morning_lp_snapshot {
-                let evening_lp_snapshot = daily_lp_iter.next();
-
+        if let Some(morning_lp_snapshot) = &morning_lp {
+            // The last snapshot of the day when it is not the first one
+            let evening_lp_snapshot = evening_lp
+                .as_ref()
+                .filter(|s| s.timestamp() > morning_lp_snapshot.timestamp());
+
+            if queue == DbQueue::HyperRoll {
+                // Hyper Roll snapshots hold the rated tier and the rating
+                let (end_tier, end_rating) = match (yesterday, evening_lp_snapshot) {
+                    (Some(true), Some(evening)) => {
+                        (evening.tier.as_str(), evening.league_points as i64)
+                    }
+                    _ => (
+                        league_details.rated_tier.as_deref().unwrap_or_default(),
+                        league_details.rated_rating.unwrap_or_default(),
+                    ),
+                };
+                let rating_change = end_rating - morning_lp_snapshot.league_points as i64;
+                let rating_change_str = match rating_change.cmp(&0) {
+                    Ordering::Greater => format!("**+{}**", rating_change),
+                    _ => format!("**{}**", rating_change),
+                };
+                banner.push_str(&format!(
+                    "\n\n`RATING INFO`\n\nstart\t{}\nend\t  {}\nGain {}",
+                    hyper_roll_rank(
+                        &morning_lp_snapshot.tier,
+                        morning_lp_snapshot.league_points as i64
+                    ),
+                    hyper_roll_rank(end_tier, end_rating),
+                    rating_change_str
+                ));
+            } else {
                 let start_rank = PlayerRank::parse_str(
                     &morning_lp_snapshot.tier,
                     &morning_lp_snapshot.rank,
                     morning_lp_snapshot.league_points,
                 );
-                let current_rank = match yesterday {
-                    Some(true) => PlayerRank::parse_str(
-                        &evening_lp_snapshot.unwrap().tier,
-                        &evening_lp_snapshot.unwrap().rank,
-                        evening_lp_snapshot.unwrap().league_points,
-                    ),
+                // Without a later snapshot that day, the current rank is the latest one known
+                let current_rank = match (yesterday, evening_lp_snapshot) {
+                    (Some(true), Some(evening)) => {
+                        PlayerRank::parse_str(&evening.tier, &evening.rank, evening.league_points)
+                    }
                     _ => PlayerRank::parse_str(
                         league_details.tier.as_ref().unwrap(),
                         league_details.rank.as_ref().unwrap(),
@@ -420,3 +477,18 @@ async fn run_tft(
     let res = InteractionResponse::new(ResponseType::ChannelMessageWithSource, banner);
     Ok(res)
 }
+
+fn queue_name(queue: DbQueue) -> &'static str {
+    match queue {
+        DbQueue::Solo => Queue::RankedSolo5x5.friendly_name(),
+        DbQueue::Flex => Queue::RankedFlex5x5.friendly_name(),
+        DbQueue::TftRanked => Queue::TFTRanked.friendly_name(),
+        DbQueue::HyperRoll => Queue::TFTHyperRoll.friendly_name(),
+        DbQueue::DoubleUp => Queue::TFTDoubleUp.friendly_name(),
+    }
+}
+
+/// Hyper Roll has rated tiers and a rating instead of divisions and LP
+fn hyper_roll_rank(tier: &str, rating: i64) -> String {
+    format!("{} ({} rating)", tier, rating)
+}
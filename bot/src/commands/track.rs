@@ -11,6 +11,13 @@ use riot_sdk::summoner::Region as SummonerRegion;
 
 pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordResponse> {
     let data = body.data.as_ref().ok_or(WinRateError::MissingData)?;
+    let guild_id = body.guild_id.as_deref().ok_or(WinRateError::MissingGuild)?;
+    // Recorded in the audit log of the guild
+    let actor = body
+        .member
+        .as_ref()
+        .map(|member| member.user.id.as_str())
+        .ok_or(WinRateError::MissingMember)?;
     let option = data.options.as_ref().ok_or(WinRateError::MissingOptions)?;
     let game_name = option
         .iter()
@@ -34,22 +41,36 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
         .as_ref()
         .ok_or(WinRateError::MissingOptionValue)?;
     let game_type = GameType::from_str(game_type.as_str().unwrap())?;
+    let queue = option
+        .iter()
+        .find(|o| o.name == "queue")
+        .and_then(|o| o.value.as_ref().and_then(|v| v.as_str()));
+    let queue = game_type.queue(queue)?;
 
     let tag_line = tag_line.as_str().unwrap();
     let game_name = game_name.as_str().unwrap();
 
     let riot_id_data = state
-        .account_client
+        .account_client(&game_type)
         .account(AccountRegion::AMERICAS)
         .get_by_riot_id(game_name, tag_line)
         .send()
         .await
         .map_err(|_| WinRateError::RiotIdNotFound)?;
     let riot_id = format!("{}#{}", riot_id_data.game_name, riot_id_data.tag_line);
+    // Keeps the name history of the player across renames
+    state
+        .lp_db_client
+        .profiles()
+        .update()
+        .puuid(&riot_id_data.puuid)
+        .riot_id(&riot_id)
+        .send()
+        .await?;
     match game_type {
         GameType::Tft => {
             let summoner_data = state
-                .league_client
+                .tft_client
                 .summoner(SummonerRegion::NA1)
                 .get_by_puuid(&riot_id_data.puuid)
                 .send()
@@ -58,10 +79,12 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
                 .lp_db_client
                 .tracking(lp_db::GameType::Tft)
                 .track_user()
-                .id(&summoner_data.id)
+                .guild_id(guild_id)
+                .actor(actor)
                 .puuid(&summoner_data.puuid)
                 .account_id(&summoner_data.account_id)
                 .summoner_name(&riot_id)
+                .queues(&[queue])
                 .send()
                 .await?;
             let banner = format!(
@@ -82,10 +105,12 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
                 .lp_db_client
                 .tracking(lp_db::GameType::League)
                 .track_user()
-                .id(&summoner_data.id)
+                .guild_id(guild_id)
+                .actor(actor)
                 .puuid(&summoner_data.puuid)
                 .account_id(&summoner_data.account_id)
                 .summoner_name(&riot_id)
+                .queues(&[queue])
                 .send()
                 .await?;
             let banner = format!(
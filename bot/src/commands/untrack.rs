@@ -7,10 +7,16 @@ use crate::discord::{
 use crate::error::Result;
 use crate::AppState;
 use riot_sdk::account::AccountRegion;
-use riot_sdk::summoner::Region as SummonerRegion;
 
 pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordResponse> {
     let data = body.data.as_ref().ok_or(WinRateError::MissingData)?;
+    let guild_id = body.guild_id.as_deref().ok_or(WinRateError::MissingGuild)?;
+    // Recorded in the audit log of the guild
+    let actor = body
+        .member
+        .as_ref()
+        .map(|member| member.user.id.as_str())
+        .ok_or(WinRateError::MissingMember)?;
     let option = data.options.as_ref().ok_or(WinRateError::MissingOptions)?;
     let game_name = option
         .iter()
@@ -40,7 +46,7 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
     let game_type = GameType::from_str(game_type.as_str().unwrap())?;
 
     let riot_id_data = state
-        .account_client
+        .account_client(&game_type)
         .account(AccountRegion::AMERICAS)
         .get_by_riot_id(game_name, tag_line)
         .send()
@@ -49,18 +55,13 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
     let riot_id = format!("{}#{}", riot_id_data.game_name, riot_id_data.tag_line);
     match game_type {
         GameType::Tft => {
-            let summoner_data = state
-                .league_client
-                .summoner(SummonerRegion::NA1)
-                .get_by_puuid(&riot_id_data.puuid)
-                .send()
-                .await?;
-
             state
                 .lp_db_client
                 .tracking(lp_db::GameType::Tft)
                 .untrack_user()
-                .id(&summoner_data.id)
+                .guild_id(guild_id)
+                .actor(actor)
+                .puuid(&riot_id_data.puuid)
                 .send()
                 .await?;
 
@@ -73,18 +74,13 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
             Ok(res)
         }
         GameType::League => {
-            let summoner_data = state
-                .league_client
-                .summoner(SummonerRegion::NA1)
-                .get_by_puuid(&riot_id_data.puuid)
-                .send()
-                .await?;
-
             state
                 .lp_db_client
                 .tracking(lp_db::GameType::League)
                 .untrack_user()
-                .id(&summoner_data.id)
+                .guild_id(guild_id)
+                .actor(actor)
+                .puuid(&riot_id_data.puuid)
                 .send()
                 .await?;
 
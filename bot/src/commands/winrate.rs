@@ -27,9 +27,9 @@ pub enum WinRateError {
     NoLeagueDetails,
     MissingOptions,
     MissingOptionValue,
-    GetItemNoTableResults,
-    GetItemNoResults,
     SummonerNotPartOfGame,
+    MissingGuild,
+    MissingMember,
 }
 
 impl Display for WinRateError {
@@ -44,9 +44,9 @@ impl Display for WinRateError {
             WinRateError::MissingData => "Missing data (from discord)",
             WinRateError::MissingOptions => "Missing options (from discord)",
             WinRateError::MissingOptionValue => "Missing option value (from discord)",
-            WinRateError::GetItemNoTableResults => "No table results from dynamodb",
-            WinRateError::GetItemNoResults => "No results from dynamodb",
             WinRateError::SummonerNotPartOfGame => "Summoner not found in game participants",
+            WinRateError::MissingGuild => "This command can only be used in a server",
+            WinRateError::MissingMember => "Missing member (from discord)",
         };
         write!(f, "{}", msg)
     }
@@ -81,7 +81,7 @@ pub async fn run(body: &DiscordPayload, state: &AppState) -> Result<DiscordRespo
     let game_type = GameType::from_str(game_type.as_str().unwrap())?;
 
     let riot_id_data = state
-        .account_client
+        .account_client(&game_type)
         .account(AccountRegion::AMERICAS)
         .get_by_riot_id(game_name, tag_line)
         .send()
@@ -148,6 +148,8 @@ async fn run_league(riot_id: &str, puuid: &str, state: &AppState) -> Result<Disc
     };
 
     let game_details = get_league_details_from_cache(&game_ids, state).await?;
+    let incomplete_warning = game_details.incomplete_warning().unwrap_or_default();
+    let game_details = game_details.games;
 
     let user_games = game_details.iter().map(|game| {
         game.info
@@ -179,13 +181,14 @@ async fn run_league(riot_id: &str, puuid: &str, state: &AppState) -> Result<Disc
     let res = InteractionResponse::new(
         ResponseType::ChannelMessageWithSource,
         format!(
-            "** --- League --- **\n\n**{}** {}\n\n[{}]: {:.2}% in last {} game(s)\n{}",
+            "** --- League --- **\n\n**{}** {}\n\n[{}]: {:.2}% in last {} game(s)\n{}{}",
             riot_id,
             league_banner,
             queue_type.friendly_name(),
             winrate,
             game_count,
-            game_lines
+            game_lines,
+            incomplete_warning
         ),
     );
     Ok(res)
@@ -241,6 +244,8 @@ async fn run_tft(riot_id: &str, puuid: &str, state: &AppState) -> Result<Discord
     };
 
     let game_details = get_tft_details_from_cache(&game_ids, state).await?;
+    let incomplete_warning = game_details.incomplete_warning().unwrap_or_default();
+    let game_details = game_details.games;
 
     let game_count = game_details.len();
 
@@ -282,8 +287,8 @@ async fn run_tft(riot_id: &str, puuid: &str, state: &AppState) -> Result<Discord
     let res = InteractionResponse::new(
         ResponseType::ChannelMessageWithSource,
         format!(
-            "** --- TFT --- **\n\n**{}** {}\n\n{:.2}% in last {} game(s)\n{}\n\n",
-            riot_id, league_banner, winrate, game_count, game_lines
+            "** --- TFT --- **\n\n**{}** {}\n\n{:.2}% in last {} game(s)\n{}{}\n\n",
+            riot_id, league_banner, winrate, game_count, game_lines, incomplete_warning
         ),
     );
     Ok(res)
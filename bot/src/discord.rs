@@ -13,6 +13,7 @@ pub enum Command {
     Track,
     Untrack,
     List,
+    Audit,
 }
 
 impl Command {
@@ -26,6 +27,13 @@ impl Command {
             _ => None,
         }
     }
+    /// Commands without a known id are matched by name
+    pub fn parse_from_name(s: &str) -> Option<Self> {
+        match s {
+            "audit" => Some(Command::Audit),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -46,22 +54,24 @@ impl FromStr for GameType {
     }
 }
 
-#[derive(Debug)]
-pub struct InteractionResponse;
-
-impl InteractionResponse {
-    pub fn new<S: Into<String>>(r#type: ResponseType, content: S) -> DiscordResponse {
-        DiscordResponse {
-            r#type: r#type.to_int(),
-            data: DiscordResponseData {
-                content: content.into(),
-                flags: 0,
-                tts: false,
-                embeds: None,
-            },
-        }
+impl GameType {
+    /// Parse the optional `queue` option of a command, the default queue of the game when unset
+    pub fn queue(&self, option: Option<&str>) -> Result<lp_db::Queue> {
+        let queue = match (self, option) {
+            (GameType::League, None | Some("solo")) => lp_db::Queue::Solo,
+            (GameType::League, Some("flex")) => lp_db::Queue::Flex,
+            (GameType::Tft, None | Some("ranked")) => lp_db::Queue::TftRanked,
+            (GameType::Tft, Some("hyper_roll")) => lp_db::Queue::HyperRoll,
+            (GameType::Tft, Some("double_up")) => lp_db::Queue::DoubleUp,
+            _ => return Err(Error::BadOption),
+        };
+        Ok(queue)
     }
 }
+
+/// Builds the response to an interaction, e.g. `InteractionResponse::new(type, content)`
+pub type InteractionResponse = DiscordResponse;
+
 pub enum ResponseType {
     Pong,
     ChannelMessageWithSource,
@@ -150,6 +160,20 @@ pub struct DiscordResponse {
     pub data: DiscordResponseData,
 }
 
+impl DiscordResponse {
+    pub fn new<S: Into<String>>(r#type: ResponseType, content: S) -> Self {
+        DiscordResponse {
+            r#type: r#type.to_int(),
+            data: DiscordResponseData {
+                content: content.into(),
+                flags: 0,
+                tts: false,
+                embeds: None,
+            },
+        }
+    }
+}
+
 impl IntoResponse for DiscordResponse {
     fn into_response(self) -> ResponseFuture {
         Box::pin(async move {
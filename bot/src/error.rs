@@ -41,6 +41,7 @@ impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::SerializeError(e) => Some(e),
+            Error::RiotApiError(e) => Some(e),
             _ => None,
         }
     }
@@ -122,6 +123,23 @@ impl IntoResponse for Error {
             Error::BadCommand => (StatusCode::BAD_REQUEST, self.to_string()),
             Error::AwsSdk(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             Error::Validation(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            // Concurrent or repeated /track and /untrack of the same player
+            Error::LeaguePointsServiceError(lp_db::error::Error::AlreadyTracked) => (
+                StatusCode::OK,
+                serde_json::to_string(&InteractionResponse::new(
+                    ResponseType::ChannelMessageWithSource,
+                    "This player is already tracked in this server",
+                ))
+                .unwrap(),
+            ),
+            Error::LeaguePointsServiceError(lp_db::error::Error::NotTracked) => (
+                StatusCode::OK,
+                serde_json::to_string(&InteractionResponse::new(
+                    ResponseType::ChannelMessageWithSource,
+                    "This player is not tracked in this server",
+                ))
+                .unwrap(),
+            ),
             Error::LeaguePointsServiceError(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 serde_json::to_string(&InteractionResponse::new(
@@ -150,7 +168,7 @@ impl IntoResponse for Error {
                 StatusCode::OK,
                 serde_json::to_string(&InteractionResponse::new(
                     ResponseType::ChannelMessageWithSource,
-                    e.to_string(),
+                    riot_error_message(&e),
                 ))
                 .unwrap(),
             ),
@@ -173,6 +191,52 @@ impl IntoResponse for Error {
         })
     }
 }
+/// Explain a Riot API error to the user that ran the command
+fn riot_error_message(e: &riot_sdk::Error) -> String {
+    use riot_sdk::{error::RateLimitScope, Endpoint};
+    match e {
+        riot_sdk::Error::NotFound {
+            resource: Endpoint::Account,
+        } => "No Riot account with this Riot ID, check the game name and tag line".to_string(),
+        riot_sdk::Error::NotFound {
+            resource: Endpoint::Summoner,
+        } => "This Riot account has no summoner on NA".to_string(),
+        riot_sdk::Error::NotFound {
+            resource: Endpoint::LeagueEntries,
+        } => "This summoner is not ranked".to_string(),
+        riot_sdk::Error::NotFound { resource } => format!("{} not found on Riot's side", resource),
+        riot_sdk::Error::RateLimited {
+            scope: RateLimitScope::Service,
+            ..
+        } => "Riot's servers are overloaded, try again in a few minutes".to_string(),
+        riot_sdk::Error::RateLimited {
+            retry_after: Some(retry_after),
+            ..
+        } => format!(
+            "Too many requests to Riot, try again in {} seconds",
+            retry_after.as_secs().max(1)
+        ),
+        riot_sdk::Error::RateLimited { .. } => {
+            "Too many requests to Riot, try again in a moment".to_string()
+        }
+        riot_sdk::Error::Status {
+            code: 401 | 403, ..
+        } => "The bot's Riot API key is invalid or expired, tell the bot owner".to_string(),
+        riot_sdk::Error::Status { code, .. } if *code >= 500 => {
+            "Riot's API is having issues, try again later".to_string()
+        }
+        riot_sdk::Error::Status { code, message } => match message {
+            Some(message) => format!("Riot API error ({}): {}", code, message),
+            None => format!("Riot API error ({})", code),
+        },
+        riot_sdk::Error::Decode { .. } => {
+            "Riot sent a response the bot does not understand (the API may have changed)"
+                .to_string()
+        }
+        riot_sdk::Error::Transport(_) => "Unable to reach Riot's API, try again later".to_string(),
+    }
+}
+
 impl From<FromHexError> for Error {
     fn from(_: FromHexError) -> Self {
         Error::BadSignature
@@ -3,11 +3,13 @@ use self::discord::{
 };
 use self::error::Error as AppError;
 use crate::discord::Command;
-use aws_sdk_dynamodb::Client as DynamoClient;
 use lambda_http::{run, service_fn, Body, IntoResponse, Request, Response};
 use lambda_runtime::Error;
+use lp_db::season::SeasonCalendar;
+use riot_sdk::cache::InMemoryCache;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
 pub mod commands;
 pub mod db;
@@ -18,10 +20,22 @@ pub type ResponseFuture = Pin<Box<dyn Future<Output = Response<Body>> + Send>>;
 
 pub struct AppState {
     account_client: riot_sdk::AccountClient,
+    /// PUUIDs are encrypted per API key, TFT players are resolved with the TFT key
+    tft_account_client: riot_sdk::AccountClient,
     league_client: riot_sdk::LeagueClient,
     tft_client: riot_sdk::TftClient,
-    db_client: DynamoClient,
     lp_db_client: lp_db::Client,
+    /// The table of the match cache written before it moved to lp-db, read on cache misses
+    legacy_match_cache: lp_db::Client,
+}
+impl AppState {
+    /// The account client using the API key of the game, whose PUUIDs its players are keyed by
+    fn account_client(&self, game_type: &discord::GameType) -> &riot_sdk::AccountClient {
+        match game_type {
+            discord::GameType::League => &self.account_client,
+            discord::GameType::Tft => &self.tft_account_client,
+        }
+    }
 }
 async fn wrapper_fn(event: Request, state: &AppState) -> Result<Response<Body>, Error> {
     let res = function_handler(event, state).await;
@@ -65,7 +79,9 @@ async fn function_handler(event: Request, state: &AppState) -> Result<DiscordRes
         1 => commands::ping::run(&body).await?,
         2 => {
             let int_data = &body.data.as_ref().ok_or(AppError::BadCommand)?;
-            let command = Command::parse_from_str(&int_data.id).ok_or(AppError::BadCommand)?;
+            let command = Command::parse_from_str(&int_data.id)
+                .or_else(|| Command::parse_from_name(&int_data.name))
+                .ok_or(AppError::BadCommand)?;
 
             match command {
                 Command::Winrate => commands::winrate::run(&body, state).await?,
@@ -73,6 +89,7 @@ async fn function_handler(event: Request, state: &AppState) -> Result<DiscordRes
                 Command::Track => commands::track::run(&body, state).await?,
                 Command::Untrack => commands::untrack::run(&body, state).await?,
                 Command::List => commands::list::run(&body, state).await?,
+                Command::Audit => commands::audit::run(&body, state).await?,
             }
         }
         _ => InteractionResponse::new(ResponseType::Pong, "Bad request type"),
@@ -93,23 +110,34 @@ async fn main() -> Result<(), Error> {
         .init();
 
     let config = aws_config::load_from_env().await;
-    let client = DynamoClient::new(&config);
 
+    // Responses are cached for as long as the lambda instance stays warm
     let state = AppState {
         account_client: riot_sdk::AccountClient::new(
             &std::env::var("RIOT_API_KEY").expect("RIOT_API_KEY not set"),
-        ),
+        )
+        .with_cache(Arc::new(InMemoryCache::new())),
+        tft_account_client: riot_sdk::AccountClient::new(
+            &std::env::var("TFT_RIOT_API_KEY").expect("TFT_RIOT_API_KEY not set"),
+        )
+        .with_cache(Arc::new(InMemoryCache::new())),
         league_client: riot_sdk::LeagueClient::new(
             &std::env::var("RIOT_API_KEY").expect("RIOT_API_KEY not set"),
-        ),
+        )
+        .with_cache(Arc::new(InMemoryCache::new())),
         tft_client: riot_sdk::TftClient::new(
             &std::env::var("TFT_RIOT_API_KEY").expect("TFT_RIOT_API_KEY not set"),
-        ),
+        )
+        .with_cache(Arc::new(InMemoryCache::new())),
         lp_db_client: lp_db::Client::new(
             &std::env::var("LP_DB_TABLE_NAME").expect("LP_DB_TABLE_NAME not set"),
             &config,
+        )
+        .with_seasons(SeasonCalendar::from_env()?),
+        legacy_match_cache: lp_db::Client::new(
+            &std::env::var("TABLE_NAME").expect("TABLE_NAME not set"),
+            &config,
         ),
-        db_client: client,
     };
 
     run(service_fn(|event: Request| async {
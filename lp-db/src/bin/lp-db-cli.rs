@@ -0,0 +1,129 @@
+//! Maintenance commands of the lp-db tables
+//!
+//! ```text
+//! lp-db-cli version [--table <name> | --sqlite <path>]
+//! lp-db-cli migrate [--dry-run] [--target <version>] [--table <name> | --sqlite <path>]
+//! ```
+//!
+//! The DynamoDB table defaults to `LP_DB_TABLE_NAME`, with the credentials of the environment.
+use lp_db::error::{Error, Result};
+use lp_db::migration::MigrationReport;
+use lp_db::Client;
+
+const USAGE: &str = "Usage:
+    lp-db-cli version [--table <name> | --sqlite <path>]
+    lp-db-cli migrate [--dry-run] [--target <version>] [--table <name> | --sqlite <path>]";
+
+enum Backend {
+    Dynamo(String),
+    #[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+    Sqlite(String),
+}
+
+struct Args {
+    command: String,
+    backend: Backend,
+    dry_run: bool,
+    target: Option<u32>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let usage = || Error::Validation(USAGE.to_string());
+    let command = args.next().ok_or_else(usage)?;
+    let mut backend = None;
+    let mut dry_run = false;
+    let mut target = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--target" => {
+                let version = args.next().ok_or_else(usage)?;
+                target = Some(version.parse().map_err(|_| usage())?);
+            }
+            "--table" => backend = Some(Backend::Dynamo(args.next().ok_or_else(usage)?)),
+            "--sqlite" => backend = Some(Backend::Sqlite(args.next().ok_or_else(usage)?)),
+            _ => return Err(usage()),
+        }
+    }
+    let backend = match backend {
+        Some(backend) => backend,
+        None => Backend::Dynamo(std::env::var("LP_DB_TABLE_NAME").map_err(|_| {
+            Error::Validation("--table, --sqlite or LP_DB_TABLE_NAME is required".to_string())
+        })?),
+    };
+    Ok(Args {
+        command,
+        backend,
+        dry_run,
+        target,
+    })
+}
+
+async fn client(backend: &Backend) -> Result<Client> {
+    match backend {
+        Backend::Dynamo(table_name) => {
+            let config = aws_config::load_from_env().await;
+            Ok(Client::new(table_name, &config))
+        }
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite(path) => Client::sqlite(path),
+        #[cfg(not(feature = "sqlite"))]
+        Backend::Sqlite(_) => Err(Error::Validation(
+            "lp-db-cli was built without the sqlite feature".to_string(),
+        )),
+    }
+}
+
+fn print_report(report: &MigrationReport) {
+    let verb = match report.dry_run {
+        true => "Would apply",
+        false => "Applied",
+    };
+    for migration in &report.migrations {
+        println!(
+            "{} migration {}: {} ({} items)",
+            verb, migration.version, migration.description, migration.items
+        );
+    }
+    match report.migrations.is_empty() {
+        true => println!("Schema version {} is up to date", report.from),
+        false => println!("Schema version {} -> {}", report.from, report.to),
+    }
+}
+
+async fn run(args: Args) -> Result<()> {
+    let client = client(&args.backend).await?;
+    match args.command.as_str() {
+        "version" => {
+            let schema = client.schema_version().send().await?;
+            println!("Schema version {}", schema.version);
+            for migration in schema.applied {
+                println!(
+                    "  {}: {} (applied at {}, {} items)",
+                    migration.version, migration.description, migration.applied_at, migration.items
+                );
+            }
+        }
+        "migrate" => {
+            let mut migrate = client.migrate().dry_run(args.dry_run);
+            if let Some(target) = args.target {
+                migrate = migrate.target(target);
+            }
+            print_report(&migrate.send().await?);
+        }
+        _ => return Err(Error::Validation(USAGE.to_string())),
+    }
+    Ok(())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let result = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => run(args).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
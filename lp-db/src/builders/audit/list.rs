@@ -0,0 +1,67 @@
+use super::{audit_partition, audit_sk, AuditEntry};
+use crate::builders::league_points::history::inclusive;
+use crate::error::Result;
+use crate::store::{paginate, ItemQuery};
+use crate::Handle;
+use futures::stream::{StreamExt, TryStreamExt};
+use std::ops::RangeBounds;
+
+/// Get the audit entries of a guild in a time range
+pub struct ListAuditBuilder {
+    handle: std::sync::Arc<Handle>,
+    guild_id: String,
+    bounds: (i64, i64),
+    /// Most entries returned
+    limit: Option<usize>,
+    newest_first: bool,
+}
+
+impl ListAuditBuilder {
+    pub(crate) fn new(
+        handle: std::sync::Arc<Handle>,
+        guild_id: &str,
+        range: impl RangeBounds<i64>,
+    ) -> Self {
+        Self {
+            handle,
+            guild_id: guild_id.to_string(),
+            bounds: inclusive(range),
+            limit: None,
+            newest_first: false,
+        }
+    }
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+    /// Newest entries first, the limit keeps the most recent ones
+    pub fn newest_first(mut self) -> Self {
+        self.newest_first = true;
+        self
+    }
+    pub async fn send(self) -> Result<Vec<AuditEntry>> {
+        let (start_time, end_time) = self.bounds;
+        if start_time > end_time {
+            return Ok(Vec::new());
+        }
+        let query = ItemQuery {
+            id: audit_partition(&self.guild_id),
+            start_sk: audit_sk(start_time),
+            // Entries are suffixed after their timestamp, nothing is stored at this key
+            end_sk: audit_sk(end_time.saturating_add(1)),
+            limit: self.limit,
+            scan_forward: !self.newest_first,
+        };
+        let handle = self.handle;
+        // A full page is followed by another one, stop once the limit is reached
+        paginate(move |start| {
+            let handle = handle.clone();
+            let query = query.clone();
+            async move { handle.store.query_items(&query, start).await }
+        })
+        .and_then(|item| async move { Ok(serde_json::from_value(item)?) })
+        .take(self.limit.unwrap_or(usize::MAX))
+        .try_collect()
+        .await
+    }
+}
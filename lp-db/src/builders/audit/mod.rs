@@ -0,0 +1,272 @@
+use crate::error::{Error, Result};
+use crate::{GameType, Handle, ItemModel};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+pub mod list;
+/// Module responsible for the audit log of the tracking and configuration changes of a guild
+///
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuditAction {
+    Track,
+    Untrack,
+    /// A setting of the guild or of a player changed
+    Config,
+}
+
+impl AuditAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Track => "TRACK",
+            AuditAction::Untrack => "UNTRACK",
+            AuditAction::Config => "CONFIG",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    #[serde(flatten)]
+    /// The item partition key
+    pub item: ItemModel,
+    pub guild_id: String,
+    /// The Discord user that made the change, `None` for changes not made by a user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    pub action: AuditAction,
+    /// The PUUID of the player, or the name of the setting for configuration changes
+    pub target: String,
+    /// The game of a tracking change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_type: Option<GameType>,
+    /// e.g. the new value of a setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub timestamp: i64,
+}
+
+// The entries of a guild are stored in the `AUDIT#<guild id>` partition, keyed by
+// `<timestamp in milliseconds>#<action>#<target>` so they sort by time. The timestamps are
+// zero-padded to 20 digits so they sort as numbers.
+
+fn audit_partition(guild_id: &str) -> String {
+    format!("AUDIT#{}", guild_id)
+}
+
+/// Sort key prefix of the entries written during the second `timestamp`, every entry written
+/// during the second sorts between `audit_sk(timestamp)` and `audit_sk(timestamp + 1)`
+fn audit_sk(timestamp: i64) -> String {
+    format!("{:020}", timestamp.saturating_mul(1000))
+}
+
+/// An audit entry timestamped now, written along with the change it records
+pub(crate) fn entry(
+    guild_id: &str,
+    actor: Option<&str>,
+    action: AuditAction,
+    target: &str,
+    game_type: Option<GameType>,
+    detail: Option<String>,
+) -> AuditEntry {
+    let now = Utc::now();
+    AuditEntry {
+        item: ItemModel {
+            id: audit_partition(guild_id),
+            sk: format!(
+                "{:020}#{}#{}",
+                now.timestamp_millis(),
+                action.as_str(),
+                target
+            ),
+        },
+        guild_id: guild_id.to_string(),
+        actor: actor.map(str::to_string),
+        action,
+        target: target.to_string(),
+        game_type,
+        detail,
+        timestamp: now.timestamp(),
+    }
+}
+
+/// Record a configuration change, tracking changes are recorded by the tracking builders
+pub struct RecordConfigBuilder {
+    handle: Arc<Handle>,
+    guild_id: Option<String>,
+    /// The Discord user that made the change
+    actor: Option<String>,
+    /// The name of the setting
+    setting: Option<String>,
+    value: Option<String>,
+}
+
+impl RecordConfigBuilder {
+    pub fn new(handle: Arc<Handle>) -> Self {
+        Self {
+            handle,
+            guild_id: None,
+            actor: None,
+            setting: None,
+            value: None,
+        }
+    }
+    pub fn guild_id(mut self, guild_id: &str) -> Self {
+        self.guild_id = Some(guild_id.to_string());
+        self
+    }
+    pub fn actor(mut self, discord_user_id: &str) -> Self {
+        self.actor = Some(discord_user_id.to_string());
+        self
+    }
+    pub fn setting(mut self, setting: &str) -> Self {
+        self.setting = Some(setting.to_string());
+        self
+    }
+    pub fn value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+    pub async fn send(self) -> Result<AuditEntry> {
+        let guild_id = self.guild_id.ok_or_else(|| Error::missing("guild_id"))?;
+        let setting = self.setting.ok_or_else(|| Error::missing("setting"))?;
+        let entry = entry(
+            &guild_id,
+            self.actor.as_deref(),
+            AuditAction::Config,
+            &setting,
+            None,
+            self.value,
+        );
+        self.handle
+            .store
+            .put_items(vec![serde_json::to_value(&entry)?])
+            .await?;
+        Ok(entry)
+    }
+}
+
+pub struct AuditClient {
+    handle: Arc<Handle>,
+}
+
+impl AuditClient {
+    pub fn new(handle: Arc<Handle>) -> Self {
+        Self { handle }
+    }
+    pub fn record_config(&self) -> RecordConfigBuilder {
+        RecordConfigBuilder::new(self.handle.clone())
+    }
+    /// The entries of a guild written in `range`, oldest first
+    pub fn list(&self, guild_id: &str, range: impl RangeBounds<i64>) -> list::ListAuditBuilder {
+        list::ListAuditBuilder::new(self.handle.clone(), guild_id, range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Client, GameType, Queue};
+
+    #[tokio::test]
+    async fn audit_log() {
+        let client = Client::in_memory();
+        let tracking = client.tracking(GameType::Tft);
+        tracking
+            .track_user()
+            .guild_id("guild")
+            .puuid("puuid")
+            .account_id("account")
+            .summoner_name("monke")
+            .queues(&[Queue::DoubleUp])
+            .actor("admin")
+            .send()
+            .await
+            .unwrap();
+        tracking
+            .untrack_user()
+            .guild_id("guild")
+            .puuid("puuid")
+            .actor("moderator")
+            .send()
+            .await
+            .unwrap();
+        // Failed changes are not recorded
+        assert!(tracking
+            .untrack_user()
+            .guild_id("guild")
+            .puuid("puuid")
+            .send()
+            .await
+            .is_err());
+        let audit = client.audit();
+        audit
+            .record_config()
+            .guild_id("other")
+            .setting("recap_channel")
+            .value("channel")
+            .send()
+            .await
+            .unwrap();
+
+        let entries = audit.list("guild", ..).send().await.unwrap();
+        let actions = entries
+            .iter()
+            .map(|entry| (entry.action, entry.actor.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actions,
+            [
+                (AuditAction::Track, Some("admin")),
+                (AuditAction::Untrack, Some("moderator"))
+            ]
+        );
+        assert_eq!(entries[0].target, "puuid");
+        assert_eq!(entries[0].game_type, Some(GameType::Tft));
+        assert_eq!(entries[0].detail.as_deref(), Some("RANKED_TFT_DOUBLE_UP"));
+        let latest = audit
+            .list("guild", ..)
+            .newest_first()
+            .limit(1)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].action, entries[1].action);
+        let timestamp = entries[0].timestamp;
+        assert!(audit
+            .list("guild", ..timestamp)
+            .send()
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            audit.list("guild", timestamp..).send().await.unwrap().len(),
+            2
+        );
+        let config = audit.list("other", ..).send().await.unwrap();
+        assert_eq!(config[0].action, AuditAction::Config);
+
+        // Timestamps with fewer digits still sort first
+        client
+            .handle
+            .store
+            .put_items(vec![serde_json::json!({
+                "id": "AUDIT#other",
+                "sk": format!("{:020}#CONFIG#language", 999_000),
+                "guildId": "other",
+                "action": "CONFIG",
+                "target": "language",
+                "timestamp": 999,
+            })])
+            .await
+            .unwrap();
+        let config = audit.list("other", ..).send().await.unwrap();
+        assert_eq!(config[0].target, "language");
+        assert_eq!(audit.list("other", ..=999).send().await.unwrap().len(), 1);
+    }
+}
@@ -1,18 +1,21 @@
-use super::LpItem;
-use crate::error::Result;
-use crate::{GameType, Handle};
-use serde_dynamo::aws_sdk_dynamodb_0_25::to_item;
+use super::{resolve_queue, tag_snapshot, LpItem};
+use crate::error::{Error, Result};
+use crate::{GameType, Handle, Queue};
 
 pub struct AddBuilder {
     handle: std::sync::Arc<Handle>,
-    /// The encrypted summoner id
-    id: Option<String>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
     timestamp: Option<i64>,
     tier: Option<String>,
     rank: Option<String>,
     league_points: Option<i32>,
     wins: Option<i64>,
     losses: Option<i64>,
+    /// Skip the write when an identical snapshot was taken since this timestamp
+    unchanged_since: Option<i64>,
     game_type: GameType,
 }
 
@@ -20,18 +23,24 @@ impl AddBuilder {
     pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
         Self {
             handle,
-            id: None,
+            puuid: None,
+            queue: None,
             timestamp: None,
             tier: None,
             rank: None,
             league_points: None,
             wins: None,
             losses: None,
+            unchanged_since: None,
             game_type,
         }
     }
-    pub fn id(mut self, id: &str) -> Self {
-        self.id = Some(id.to_string());
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
         self
     }
     pub fn timestamp(mut self, timestamp: i64) -> Self {
@@ -58,24 +67,65 @@ impl AddBuilder {
         self.losses = Some(losses);
         self
     }
-    pub async fn send(self) -> Result<()> {
-        let item = LpItem::new(
-            self.game_type,
-            &self.id.expect("id is required"),
-            self.timestamp.expect("timestamp is required"),
-            &self.tier.expect("tier is required"),
-            &self.rank.expect("rank is required"),
-            self.league_points.expect("league_points is required"),
-            self.wins.expect("wins is required"),
-            self.losses.expect("losses is required"),
+    /// Only write the snapshot if the latest snapshot written with this option does not hold the
+    /// same tier, rank, league points, wins and losses, or was taken before `timestamp`
+    ///
+    /// Makes retried or repeated writes for the same day or interval idempotent, `timestamp`
+    /// should not be before the start of the day so that the first snapshot of each day is
+    /// written. The check and the write are a single conditional write, concurrent writes of the
+    /// same standing write it once.
+    pub fn skip_unchanged_since(mut self, timestamp: i64) -> Self {
+        self.unchanged_since = Some(timestamp);
+        self
+    }
+    /// Returns `false` when the write was skipped by [`Self::skip_unchanged_since`]
+    pub async fn send(self) -> Result<bool> {
+        let queue = resolve_queue(self.game_type, self.queue)?;
+        let mut item = LpItem::new(
+            queue,
+            &self.puuid.ok_or_else(|| Error::missing("puuid"))?,
+            self.timestamp.ok_or_else(|| Error::missing("timestamp"))?,
+            &self.tier.ok_or_else(|| Error::missing("tier"))?,
+            &self.rank.ok_or_else(|| Error::missing("rank"))?,
+            self.league_points
+                .ok_or_else(|| Error::missing("league_points"))?,
+            self.wins.ok_or_else(|| Error::missing("wins"))?,
+            self.losses.ok_or_else(|| Error::missing("losses"))?,
         );
-        self.handle
-            .inner
-            .put_item()
-            .table_name(self.handle.table_name.as_str())
-            .set_item(Some(to_item(&item).unwrap()))
+        tag_snapshot(&self.handle, &mut item);
+        match self.unchanged_since {
+            Some(since) => self.handle.store.add_unless_unchanged(item, since).await,
+            None => {
+                self.handle.store.add(item).await?;
+                Ok(true)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+
+    #[tokio::test]
+    async fn missing_field() {
+        let client = Client::in_memory();
+        let res = client
+            .league_points(GameType::League)
+            .add()
+            .puuid("summoner")
+            .timestamp(1)
+            .send()
+            .await;
+        assert!(matches!(res, Err(Error::Validation(e)) if e == "tier is required"));
+        let res = client
+            .league_points(GameType::League)
+            .get_between()
+            .start_time(0)
+            .end_time(1)
             .send()
-            .await?;
-        Ok(())
+            .await;
+        assert!(matches!(res, Err(Error::Validation(_))));
     }
 }
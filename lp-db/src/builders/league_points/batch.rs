@@ -0,0 +1,242 @@
+use super::between::GetBetweenBuilder;
+use super::{resolve_queue, tag_snapshot, LpItem};
+use crate::error::{Error, Result};
+use crate::{check_queue, GameType, Handle, Queue};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::collections::BTreeSet;
+
+/// Most requests in flight for the snapshots written or read one by one, so that large guilds
+/// do not exceed the provisioned throughput of the table
+const CONCURRENCY: usize = 10;
+
+/// Write many snapshots, see [`super::LeaguePointClient::add_many`]
+pub struct AddManyBuilder {
+    handle: std::sync::Arc<Handle>,
+    items: Vec<LpItem>,
+    /// Skip the snapshots unchanged since this timestamp
+    unchanged_since: Option<i64>,
+    game_type: GameType,
+}
+
+impl AddManyBuilder {
+    pub(crate) fn new(
+        handle: std::sync::Arc<Handle>,
+        game_type: GameType,
+        items: Vec<LpItem>,
+    ) -> Self {
+        Self {
+            handle,
+            items,
+            unchanged_since: None,
+            game_type,
+        }
+    }
+    /// Same as [`super::add::AddBuilder::skip_unchanged_since`] for every snapshot
+    pub fn skip_unchanged_since(mut self, timestamp: i64) -> Self {
+        self.unchanged_since = Some(timestamp);
+        self
+    }
+    /// Returns the number of written snapshots
+    ///
+    /// The DynamoDB store writes by batches of 25 items and retries the items left unprocessed.
+    /// With [`Self::skip_unchanged_since`] every snapshot is a conditional write of its own, a few
+    /// at a time, lp-serv always sets it and never writes by batches. Of the snapshots with the
+    /// same player, queue and timestamp only the last one is written.
+    pub async fn send(self) -> Result<usize> {
+        for item in &self.items {
+            let queue = item
+                .queue()
+                .ok_or_else(|| Error::Validation(format!("{} is not a snapshot", item.item.sk)))?;
+            check_queue(self.game_type, queue)?;
+        }
+        // DynamoDB rejects batches with duplicate keys
+        let mut keys = BTreeSet::new();
+        let mut items: Vec<LpItem> = self
+            .items
+            .into_iter()
+            .rev()
+            .filter(|item| keys.insert((item.item.id.clone(), item.item.sk.clone())))
+            .collect();
+        items.reverse();
+        for item in &mut items {
+            tag_snapshot(&self.handle, item);
+        }
+        if let Some(since) = self.unchanged_since {
+            let handle = &self.handle;
+            let written: Vec<bool> = stream::iter(items)
+                .map(|item| handle.store.add_unless_unchanged(item, since))
+                .buffer_unordered(CONCURRENCY)
+                .try_collect()
+                .await?;
+            return Ok(written.into_iter().filter(|written| *written).count());
+        }
+        let written = items.len();
+        if written > 0 {
+            self.handle.store.add_many(items).await?;
+        }
+        Ok(written)
+    }
+}
+
+/// Get a snapshot of many players, e.g. for leaderboards
+pub struct GetManyBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUIDs of the players
+    puuids: Vec<String>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
+    /// Get the snapshots taken at this timestamp instead of the latest ones
+    timestamp: Option<i64>,
+    game_type: GameType,
+}
+
+impl GetManyBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
+        Self {
+            handle,
+            puuids: Vec::new(),
+            queue: None,
+            timestamp: None,
+            game_type,
+        }
+    }
+    pub fn puuids(mut self, puuids: &[String]) -> Self {
+        self.puuids = puuids.to_vec();
+        self
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+    /// lp-serv takes the snapshots of every player at the same timestamp, getting them by
+    /// timestamp is a single batch request (per 100 players) with the DynamoDB store
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+    /// Players without a snapshot are skipped, each player is returned once
+    ///
+    /// Without a timestamp, the latest snapshot of each player is queried a few at a time.
+    pub async fn send(self) -> Result<Vec<LpItem>> {
+        let queue = resolve_queue(self.game_type, self.queue)?;
+        // DynamoDB rejects batches with duplicate keys
+        let mut seen = BTreeSet::new();
+        let puuids: Vec<String> = self
+            .puuids
+            .into_iter()
+            .filter(|puuid| seen.insert(puuid.clone()))
+            .collect();
+        if let Some(timestamp) = self.timestamp {
+            return self.handle.store.get_many(queue, &puuids, timestamp).await;
+        }
+        let latest: Vec<_> = stream::iter(&puuids)
+            .map(|puuid| {
+                GetBetweenBuilder::new(self.handle.clone(), self.game_type)
+                    .puuid(puuid)
+                    .queue(queue)
+                    .start_time(0)
+                    .end_time(i64::MAX)
+                    .scan_forward(false)
+                    .limit(1)
+                    .send()
+            })
+            .buffered(CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(latest.into_iter().flatten().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+
+    #[tokio::test]
+    async fn batch_write_and_get() {
+        const T: i64 = 1704085200;
+        let client = Client::in_memory();
+        let league_points = client.league_points(GameType::League);
+        let puuids: Vec<String> = (0..30).map(|i| format!("puuid-{}", i)).collect();
+        let snapshots = |timestamp| {
+            puuids
+                .iter()
+                .map(|puuid| LpItem::new(Queue::Solo, puuid, timestamp, "GOLD", "I", 10, 5, 5))
+                .collect::<Vec<_>>()
+        };
+        let written = league_points
+            .add_many(snapshots(T))
+            .skip_unchanged_since(T)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(written, 30);
+        let written = league_points
+            .add_many(snapshots(T + 60))
+            .skip_unchanged_since(T)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(written, 0);
+        league_points
+            .add()
+            .puuid("puuid-0")
+            .timestamp(T + 120)
+            .tier("GOLD")
+            .rank("I")
+            .league_points(30)
+            .wins(6)
+            .losses(5)
+            .send()
+            .await
+            .unwrap();
+
+        let mut wanted = puuids[..2].to_vec();
+        wanted.push("unknown".to_string());
+        wanted.push(puuids[0].clone());
+        let at = league_points
+            .get_many()
+            .puuids(&wanted)
+            .timestamp(T)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(at.len(), 2);
+        let latest = league_points
+            .get_many()
+            .puuids(&wanted)
+            .send()
+            .await
+            .unwrap();
+        let points: Vec<_> = latest.iter().map(|lp| lp.league_points).collect();
+        assert_eq!(points, vec![30, 10]);
+
+        // Of the snapshots with the same key the last one is written
+        let duplicates = vec![
+            LpItem::new(Queue::Solo, "puuid-1", T + 180, "GOLD", "I", 20, 6, 5),
+            LpItem::new(Queue::Solo, "puuid-1", T + 180, "GOLD", "I", 40, 7, 5),
+        ];
+        let written = league_points.add_many(duplicates).send().await.unwrap();
+        assert_eq!(written, 1);
+        let latest = league_points
+            .get_many()
+            .puuids(&wanted[1..2])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(latest[0].league_points, 40);
+
+        let tft = vec![LpItem::new(
+            Queue::TftRanked,
+            "puuid",
+            T,
+            "GOLD",
+            "I",
+            10,
+            5,
+            5,
+        )];
+        let res = league_points.add_many(tft).send().await;
+        assert!(matches!(res, Err(Error::Validation(_))));
+    }
+}
@@ -1,17 +1,23 @@
-use super::LpItem;
-use crate::error::Result;
-use crate::{ident, GameType, Handle};
-use aws_sdk_dynamodb::types::AttributeValue;
-use serde_dynamo::aws_sdk_dynamodb_0_25::from_items;
+use super::{resolve_queue, LpItem};
+use crate::error::{Error, Result};
+use crate::store::{paginate, BetweenQuery};
+use crate::{GameType, Handle, Queue};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 
 pub struct GetBetweenBuilder {
     handle: std::sync::Arc<Handle>,
-    /// The encrypted summoner id
-    id: Option<String>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
     /// The starting timestamp of the query
     start_time: Option<i64>,
     /// The ending timestamp of the query
     end_time: Option<i64>,
+    /// The maximum number of snapshots returned
+    limit: Option<usize>,
+    /// Oldest snapshots first, defaults to `true`
+    scan_forward: bool,
     game_type: GameType,
 }
 
@@ -19,14 +25,21 @@ impl GetBetweenBuilder {
     pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
         Self {
             handle,
-            id: None,
+            puuid: None,
+            queue: None,
             start_time: None,
             end_time: None,
+            limit: None,
+            scan_forward: true,
             game_type,
         }
     }
-    pub fn id(mut self, id: &str) -> Self {
-        self.id = Some(id.to_string());
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
         self
     }
     pub fn start_time(mut self, timestamp: i64) -> Self {
@@ -37,33 +50,85 @@ impl GetBetweenBuilder {
         self.end_time = Some(timestamp);
         self
     }
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+    /// Set to `false` to get the latest snapshots first, e.g. with `limit` to get the latest N
+    pub fn scan_forward(mut self, scan_forward: bool) -> Self {
+        self.scan_forward = scan_forward;
+        self
+    }
+    fn query(&self) -> Result<BetweenQuery> {
+        Ok(BetweenQuery {
+            queue: resolve_queue(self.game_type, self.queue)?,
+            puuid: self.puuid.clone().ok_or_else(|| Error::missing("puuid"))?,
+            start_time: self
+                .start_time
+                .ok_or_else(|| Error::missing("start_time"))?,
+            end_time: self.end_time.ok_or_else(|| Error::missing("end_time"))?,
+            limit: self.limit,
+            scan_forward: self.scan_forward,
+        })
+    }
+    /// Stream the snapshots, fetching the next page only once the current one is consumed
+    ///
+    /// A builder missing required fields yields a single validation error.
+    pub fn into_stream(self) -> impl Stream<Item = Result<LpItem>> {
+        let query = match self.query() {
+            Ok(query) => query,
+            Err(e) => return stream::once(async { Err(e) }).left_stream(),
+        };
+        let handle = self.handle;
+        paginate(move |start| {
+            let handle = handle.clone();
+            let query = query.clone();
+            async move { handle.store.get_between(&query, start).await }
+        })
+        .take(self.limit.unwrap_or(usize::MAX))
+        .right_stream()
+    }
     pub async fn send(self) -> Result<Option<Vec<LpItem>>> {
-        let id = self.id.expect("id is required");
-        let start_time = self.start_time.expect("start_time is required");
-        let end_time = self.end_time.expect("end_time is required");
+        Ok(Some(self.into_stream().try_collect().await?))
+    }
+}
 
-        let ident = ident(self.game_type);
-        let res = self
-            .handle
-            .inner
-            .query()
-            .table_name(self.handle.table_name.as_str())
-            .key_condition_expression("id = :id AND sk BETWEEN :start_time AND :end_time")
-            .expression_attribute_values(":id", AttributeValue::S(id))
-            .expression_attribute_values(
-                ":start_time",
-                AttributeValue::S(format!("#{}{}", ident, start_time)),
-            )
-            .expression_attribute_values(
-                ":end_time",
-                AttributeValue::S(format!("#{}{}", ident, end_time)),
-            )
-            .send()
-            .await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
 
-        match res.items() {
-            Some(items) => Ok(Some(from_items(items.to_vec()).unwrap())),
-            None => Ok(None),
+    #[tokio::test]
+    async fn latest_snapshots() {
+        let client = Client::in_memory();
+        for timestamp in 1..=5 {
+            client
+                .league_points(GameType::League)
+                .add()
+                .puuid("summoner")
+                .timestamp(timestamp)
+                .tier("GOLD")
+                .rank("II")
+                .league_points(timestamp as i32)
+                .wins(10)
+                .losses(5)
+                .send()
+                .await
+                .unwrap();
         }
+        let latest = client
+            .league_points(GameType::League)
+            .get_between()
+            .puuid("summoner")
+            .start_time(0)
+            .end_time(9)
+            .scan_forward(false)
+            .limit(2)
+            .send()
+            .await
+            .unwrap()
+            .unwrap();
+        let points: Vec<_> = latest.iter().map(|lp| lp.league_points).collect();
+        assert_eq!(points, vec![5, 4]);
     }
 }
@@ -0,0 +1,193 @@
+use super::between::GetBetweenBuilder;
+use super::{resolve_queue, LpItem};
+use crate::error::{Error, Result};
+use crate::{GameType, Handle, Queue};
+use chrono::{TimeZone, Utc};
+
+/// Snapshots identical to the previous one taken within this many seconds are redundant
+pub const DEFAULT_WINDOW: i64 = 60 * 60;
+
+/// Remove the redundant snapshots of a player
+///
+/// A snapshot is redundant when it holds the same standing as the previous kept snapshot and
+/// was taken less than `window` seconds after it on the same day, e.g. by a retried or manual
+/// lp-serv invocation. Snapshots of different days are never redundant, so the scheduled
+/// snapshots around midnight are kept even when nothing changed, see [`Self::timezone`].
+pub struct CompactBuilder<Tz: TimeZone = Utc> {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
+    /// Defaults to [`DEFAULT_WINDOW`]
+    window: i64,
+    /// The time zone of the days, defaults to UTC
+    tz: Tz,
+    game_type: GameType,
+}
+
+impl CompactBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
+        Self {
+            handle,
+            puuid: None,
+            queue: None,
+            window: DEFAULT_WINDOW,
+            tz: Utc,
+            game_type,
+        }
+    }
+}
+
+impl<Tz: TimeZone> CompactBuilder<Tz> {
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+    /// In seconds
+    pub fn window(mut self, window: i64) -> Self {
+        self.window = window;
+        self
+    }
+    /// The time zone of the lp-serv schedule, the snapshots of different days in it are kept
+    pub fn timezone<T: TimeZone>(self, tz: &T) -> CompactBuilder<T> {
+        CompactBuilder {
+            handle: self.handle,
+            puuid: self.puuid,
+            queue: self.queue,
+            window: self.window,
+            tz: tz.clone(),
+            game_type: self.game_type,
+        }
+    }
+    /// Returns the number of removed snapshots
+    pub async fn send(self) -> Result<usize> {
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        let queue = resolve_queue(self.game_type, self.queue)?;
+        let snapshots = GetBetweenBuilder::new(self.handle.clone(), self.game_type)
+            .puuid(&puuid)
+            .queue(queue)
+            .start_time(0)
+            .end_time(i64::MAX)
+            .send()
+            .await?
+            .unwrap_or_default();
+
+        let mut removed = 0;
+        let day = |snapshot: &LpItem| snapshot.timestamp().with_timezone(&self.tz).date_naive();
+        let mut kept: Option<&LpItem> = None;
+        for snapshot in &snapshots {
+            let timestamp = snapshot.timestamp().timestamp();
+            match kept {
+                Some(previous)
+                    if snapshot.same_standing(previous)
+                        && timestamp - previous.timestamp().timestamp() < self.window
+                        && day(snapshot) == day(previous) =>
+                {
+                    self.handle.store.remove(queue, &puuid, timestamp).await?;
+                    removed += 1;
+                }
+                _ => kept = Some(snapshot),
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+    use chrono::FixedOffset;
+
+    #[tokio::test]
+    async fn idempotent_writes_and_compaction() {
+        const T: i64 = 1704085200;
+        let client = Client::in_memory();
+        let league_points = client.league_points(GameType::League);
+        let add = |timestamp, league_points_value| {
+            league_points
+                .add()
+                .puuid("puuid")
+                .timestamp(timestamp)
+                .tier("GOLD")
+                .rank("II")
+                .league_points(league_points_value)
+                .wins(10)
+                .losses(5)
+        };
+        assert!(add(T, 50).skip_unchanged_since(T).send().await.unwrap());
+        assert!(!add(T + 100, 50)
+            .skip_unchanged_since(T)
+            .send()
+            .await
+            .unwrap());
+        assert!(add(T + 200, 60)
+            .skip_unchanged_since(T)
+            .send()
+            .await
+            .unwrap());
+        let history = league_points.history("puuid", ..).send().await.unwrap();
+        assert_eq!(history.len(), 2);
+
+        // A retry right after the snapshot and the next day's unchanged snapshot
+        add(T + 300, 60).send().await.unwrap();
+        add(T + 86_400, 60).send().await.unwrap();
+        let removed = league_points.compact().puuid("puuid").send().await.unwrap();
+        assert_eq!(removed, 1);
+        let history = league_points.history("puuid", ..).send().await.unwrap();
+        let timestamps: Vec<_> = history
+            .iter()
+            .map(|snapshot| snapshot.timestamp().timestamp())
+            .collect();
+        assert_eq!(timestamps, vec![T, T + 200, T + 86_400]);
+
+        // Concurrent writes of the same standing write it once
+        let (first, second) = futures::join!(
+            add(T + 90_000, 70).skip_unchanged_since(T).send(),
+            add(T + 90_060, 70).skip_unchanged_since(T).send(),
+        );
+        assert!(first.unwrap() ^ second.unwrap());
+
+        // The scheduled snapshots at 23:59 and 00:00 in UTC-5 are on different days
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let night = |timestamp| {
+            league_points
+                .add()
+                .puuid("night")
+                .timestamp(timestamp)
+                .tier("GOLD")
+                .rank("II")
+                .league_points(50)
+                .wins(10)
+                .losses(5)
+        };
+        let since = |timestamp: i64, midnight| (timestamp - DEFAULT_WINDOW).max(midnight);
+        assert!(night(T - 60)
+            .skip_unchanged_since(since(T - 60, T - 86_400))
+            .send()
+            .await
+            .unwrap());
+        assert!(night(T)
+            .skip_unchanged_since(since(T, T))
+            .send()
+            .await
+            .unwrap());
+        // A retry of the midnight run
+        assert!(!night(T + 30)
+            .skip_unchanged_since(since(T + 30, T))
+            .send()
+            .await
+            .unwrap());
+        let compact = || league_points.compact().puuid("night");
+        assert_eq!(compact().timezone(&tz).send().await.unwrap(), 0);
+        let history = league_points.history("night", ..).send().await.unwrap();
+        assert_eq!(history.len(), 2);
+        // Both are on the same UTC day
+        assert_eq!(compact().send().await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,244 @@
+use super::{day_key, resolve_queue, LpItem};
+use crate::error::Result;
+use crate::store::{paginate, DayQuery};
+use crate::{GameType, Handle, Queue};
+use futures::stream::TryStreamExt;
+use std::collections::BTreeMap;
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// Get the snapshots of every player taken between two timestamps, with one query per UTC day
+/// on the `byDay` index
+async fn snapshots_between(
+    handle: &std::sync::Arc<Handle>,
+    game_type: GameType,
+    queue: Queue,
+    (start_time, end_time): (i64, i64),
+) -> Result<Vec<LpItem>> {
+    let mut snapshots = Vec::new();
+    let mut day_start = start_time.div_euclid(DAY) * DAY;
+    while day_start <= end_time {
+        let Some(day) = day_key(game_type, day_start) else {
+            break;
+        };
+        let query = DayQuery {
+            queue,
+            day,
+            start_time: start_time.max(day_start),
+            end_time: end_time.min(day_start + DAY - 1),
+            limit: None,
+        };
+        let handle = handle.clone();
+        let day_snapshots: Vec<LpItem> = paginate(move |start| {
+            let handle = handle.clone();
+            let query = query.clone();
+            async move { handle.store.get_day(&query, start).await }
+        })
+        .try_collect()
+        .await?;
+        snapshots.extend(day_snapshots);
+        day_start += DAY;
+    }
+    Ok(snapshots)
+}
+
+/// Get the snapshots of every player taken during a day, oldest first
+pub struct DaySnapshotsBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The first and last timestamps of the day
+    bounds: Result<(i64, i64)>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
+    game_type: GameType,
+}
+
+impl DaySnapshotsBuilder {
+    pub(crate) fn new(
+        handle: std::sync::Arc<Handle>,
+        game_type: GameType,
+        bounds: Result<(i64, i64)>,
+    ) -> Self {
+        Self {
+            handle,
+            bounds,
+            queue: None,
+            game_type,
+        }
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+    pub async fn send(self) -> Result<Vec<LpItem>> {
+        let queue = resolve_queue(self.game_type, self.queue)?;
+        snapshots_between(&self.handle, self.game_type, queue, self.bounds?).await
+    }
+}
+
+/// The first and last snapshots of a player during a day
+#[derive(Debug)]
+pub struct DailyStanding {
+    pub first: LpItem,
+    pub last: LpItem,
+}
+
+impl DailyStanding {
+    /// The PUUID of the player
+    pub fn puuid(&self) -> &str {
+        self.last.puuid()
+    }
+    /// The rank points gained during the day, negative when the player lost some
+    pub fn climb(&self) -> i32 {
+        self.last.rank_points() - self.first.rank_points()
+    }
+    pub fn games_played(&self) -> i64 {
+        self.last.games_played_since(&self.first)
+    }
+}
+
+/// Get the standing of every player with a snapshot during a day, e.g. for server-wide daily
+/// leaderboards
+pub struct DailyStandingsBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The first and last timestamps of the day
+    bounds: Result<(i64, i64)>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
+    /// Order by climb instead of by final rank
+    by_climb: bool,
+    game_type: GameType,
+}
+
+impl DailyStandingsBuilder {
+    pub(crate) fn new(
+        handle: std::sync::Arc<Handle>,
+        game_type: GameType,
+        bounds: Result<(i64, i64)>,
+    ) -> Self {
+        Self {
+            handle,
+            bounds,
+            queue: None,
+            by_climb: false,
+            game_type,
+        }
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+    /// Biggest climbers first, see [`DailyStanding::climb`]
+    pub fn by_climb(mut self) -> Self {
+        self.by_climb = true;
+        self
+    }
+    /// Highest final rank first, unless ordered [`Self::by_climb`]
+    pub async fn send(self) -> Result<Vec<DailyStanding>> {
+        let queue = resolve_queue(self.game_type, self.queue)?;
+        let snapshots =
+            snapshots_between(&self.handle, self.game_type, queue, self.bounds?).await?;
+
+        let mut players: BTreeMap<String, (LpItem, Option<LpItem>)> = BTreeMap::new();
+        for snapshot in snapshots {
+            match players.get_mut(snapshot.puuid()) {
+                Some((_, last)) => *last = Some(snapshot),
+                None => {
+                    players.insert(snapshot.puuid().to_string(), (snapshot, None));
+                }
+            }
+        }
+        let mut standings = players
+            .into_values()
+            .map(|(first, last)| {
+                // A single snapshot is both the first and last one
+                let last = last.unwrap_or_else(|| first.clone());
+                DailyStanding { first, last }
+            })
+            .collect::<Vec<_>>();
+        match self.by_climb {
+            true => standings.sort_by_key(|standing| std::cmp::Reverse(standing.climb())),
+            false => {
+                standings.sort_by_key(|standing| std::cmp::Reverse(standing.last.rank_points()))
+            }
+        }
+        Ok(standings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::league_points::LpItem;
+    use crate::store::{LpStore, MemoryStore};
+    use crate::{Client, GameType, Queue};
+    use chrono::{FixedOffset, NaiveDate};
+
+    #[tokio::test]
+    async fn daily_standings() {
+        const T: i64 = 1704085200;
+        let store = MemoryStore::new();
+        // Written before the byDay index, without a day
+        let mut legacy = LpItem::new(Queue::Solo, "c", T + 3600, "SILVER", "I", 95, 30, 30);
+        legacy.day = None;
+        store
+            .put_items(vec![serde_json::to_value(&legacy).unwrap()])
+            .await
+            .unwrap();
+        let client = Client::from_store(store);
+        client
+            .tracking(GameType::League)
+            .track_user()
+            .guild_id("guild")
+            .puuid("c")
+            .account_id("account")
+            .summoner_name("monke")
+            .send()
+            .await
+            .unwrap();
+        let snapshots = vec![
+            LpItem::new(Queue::Solo, "a", T + 3600, "GOLD", "II", 50, 10, 5),
+            // The next UTC day, still the same day in the Eastern time zone
+            LpItem::new(Queue::Solo, "a", T + 22 * 3600, "GOLD", "I", 20, 13, 5),
+            LpItem::new(Queue::Flex, "a", T + 100, "IRON", "IV", 0, 0, 1),
+            LpItem::new(Queue::Solo, "b", T + 7200, "PLATINUM", "IV", 10, 20, 20),
+            LpItem::new(Queue::Solo, "c", T - 3600, "SILVER", "I", 90, 29, 30),
+        ];
+        let league_points = client.league_points(GameType::League);
+        league_points.add_many(snapshots).send().await.unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let day = league_points.day(date, &tz).send().await.unwrap();
+        assert_eq!(day.len(), 3);
+        let standings = league_points
+            .daily_standings(date, &tz)
+            .send()
+            .await
+            .unwrap();
+        let puuids = |standings: &[DailyStanding]| {
+            standings
+                .iter()
+                .map(|standing| standing.puuid().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(puuids(&standings), ["b", "a"]);
+        assert_eq!(standings[1].climb(), 70);
+        assert_eq!(standings[1].games_played(), 3);
+        let climbers = league_points
+            .daily_standings(date, &tz)
+            .by_climb()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(puuids(&climbers), ["a", "b"]);
+
+        client.migrate().send().await.unwrap();
+        let standings = league_points
+            .daily_standings(date, &tz)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(puuids(&standings), ["b", "a", "c"]);
+        assert_eq!(standings[2].first.league_points, 95);
+    }
+}
@@ -1,13 +1,13 @@
-use super::LpItem;
-use crate::error::Result;
-use crate::{ident, GameType, Handle};
-use aws_sdk_dynamodb::types::AttributeValue;
-use serde_dynamo::aws_sdk_dynamodb_0_25::from_item;
+use super::{resolve_queue, LpItem};
+use crate::error::{Error, Result};
+use crate::{GameType, Handle, Queue};
 
 pub struct GetBuilder {
     handle: std::sync::Arc<Handle>,
-    /// The encrypted summoner id
-    id: Option<String>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
     timestamp: Option<i64>,
     game_type: GameType,
 }
@@ -16,13 +16,18 @@ impl GetBuilder {
     pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
         Self {
             handle,
-            id: None,
+            puuid: None,
+            queue: None,
             timestamp: None,
             game_type,
         }
     }
-    pub fn id(mut self, id: &str) -> Self {
-        self.id = Some(id.to_string());
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
         self
     }
     pub fn timestamp(mut self, timestamp: i64) -> Self {
@@ -30,24 +35,9 @@ impl GetBuilder {
         self
     }
     pub async fn send(self) -> Result<Option<LpItem>> {
-        let id = self.id.expect("id is required");
-        let timestamp = self.timestamp.expect("timestamp is required");
-        let ident = ident(self.game_type);
-        let sk = format!("#{}{}", ident, timestamp);
-
-        let res = self
-            .handle
-            .inner
-            .get_item()
-            .table_name(self.handle.table_name.as_str())
-            .key("id", AttributeValue::S(id))
-            .key("sk", AttributeValue::S(sk))
-            .send()
-            .await?;
-
-        match res.item() {
-            Some(item) => Ok(Some(from_item(item.clone()).unwrap())),
-            None => Ok(None),
-        }
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        let timestamp = self.timestamp.ok_or_else(|| Error::missing("timestamp"))?;
+        let queue = resolve_queue(self.game_type, self.queue)?;
+        self.handle.store.get(queue, &puuid, timestamp).await
     }
 }
@@ -0,0 +1,258 @@
+use super::between::GetBetweenBuilder;
+use super::LpItem;
+use crate::error::{Error, Result};
+use crate::{GameType, Handle, Queue};
+use chrono::{Days, NaiveDate, TimeZone};
+use std::ops::{Bound, RangeBounds};
+
+/// Find a single snapshot of a player, see [`super::LeaguePointClient::latest`],
+/// [`super::LeaguePointClient::at_or_before`] and [`super::LeaguePointClient::first_of_day`]
+pub struct FindSnapshotBuilder {
+    handle: std::sync::Arc<Handle>,
+    puuid: String,
+    /// `Err` when the requested day has no valid local time
+    bounds: Result<(i64, i64)>,
+    scan_forward: bool,
+    queue: Option<Queue>,
+    /// Only search the split running at this timestamp
+    split_of: Option<i64>,
+    game_type: GameType,
+}
+
+impl FindSnapshotBuilder {
+    pub(crate) fn new(
+        handle: std::sync::Arc<Handle>,
+        game_type: GameType,
+        puuid: &str,
+        bounds: Result<(i64, i64)>,
+        scan_forward: bool,
+    ) -> Self {
+        Self {
+            handle,
+            puuid: puuid.to_string(),
+            bounds,
+            scan_forward,
+            queue: None,
+            split_of: None,
+            game_type,
+        }
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+    /// Ignore the snapshots of other splits than the one running at `timestamp`, so that ranks
+    /// are not compared across a ranked reset
+    ///
+    /// Has no effect unless the client has a season calendar, see [`crate::Client::with_seasons`].
+    pub fn within_split_of(mut self, timestamp: i64) -> Self {
+        self.split_of = Some(timestamp);
+        self
+    }
+    pub async fn send(self) -> Result<Option<LpItem>> {
+        let bounds = split_bounds(&self.handle, self.game_type, self.bounds?, self.split_of);
+        if bounds.0 > bounds.1 {
+            return Ok(None);
+        }
+        let snapshots = between(
+            &self.handle,
+            self.game_type,
+            &self.puuid,
+            bounds,
+            self.queue,
+        )
+        .scan_forward(self.scan_forward)
+        .limit(1)
+        .send()
+        .await?
+        .unwrap_or_default();
+        Ok(snapshots.into_iter().next())
+    }
+}
+
+/// Get the snapshots of a player in a time range, oldest first
+pub struct HistoryBuilder {
+    handle: std::sync::Arc<Handle>,
+    puuid: String,
+    bounds: (i64, i64),
+    queue: Option<Queue>,
+    /// Only get the snapshots of the split running at this timestamp
+    split_of: Option<i64>,
+    game_type: GameType,
+}
+
+impl HistoryBuilder {
+    pub(crate) fn new(
+        handle: std::sync::Arc<Handle>,
+        game_type: GameType,
+        puuid: &str,
+        range: impl RangeBounds<i64>,
+    ) -> Self {
+        Self {
+            handle,
+            puuid: puuid.to_string(),
+            bounds: inclusive(range),
+            queue: None,
+            split_of: None,
+            game_type,
+        }
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+    /// Same as [`FindSnapshotBuilder::within_split_of`]
+    pub fn within_split_of(mut self, timestamp: i64) -> Self {
+        self.split_of = Some(timestamp);
+        self
+    }
+    pub async fn send(self) -> Result<Vec<LpItem>> {
+        let bounds = split_bounds(&self.handle, self.game_type, self.bounds, self.split_of);
+        if bounds.0 > bounds.1 {
+            return Ok(Vec::new());
+        }
+        let snapshots = between(
+            &self.handle,
+            self.game_type,
+            &self.puuid,
+            bounds,
+            self.queue,
+        )
+        .send()
+        .await?;
+        Ok(snapshots.unwrap_or_default())
+    }
+}
+
+fn between(
+    handle: &std::sync::Arc<Handle>,
+    game_type: GameType,
+    puuid: &str,
+    (start_time, end_time): (i64, i64),
+    queue: Option<Queue>,
+) -> GetBetweenBuilder {
+    let builder = GetBetweenBuilder::new(handle.clone(), game_type)
+        .puuid(puuid)
+        .start_time(start_time)
+        .end_time(end_time);
+    match queue {
+        Some(queue) => builder.queue(queue),
+        None => builder,
+    }
+}
+
+/// The bounds narrowed to a split, empty (`start > end`) when the range is outside the split
+fn split_bounds(
+    handle: &Handle,
+    game_type: GameType,
+    bounds: (i64, i64),
+    split_of: Option<i64>,
+) -> (i64, i64) {
+    match split_of {
+        Some(timestamp) => handle.seasons.clamp(game_type, timestamp, bounds),
+        None => bounds,
+    }
+}
+
+/// Convert a range of timestamps to the inclusive bounds of `get_between`
+pub(crate) fn inclusive(range: impl RangeBounds<i64>) -> (i64, i64) {
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => *end,
+        Bound::Excluded(end) => end.saturating_sub(1),
+        Bound::Unbounded => i64::MAX,
+    };
+    (start, end)
+}
+
+/// The timestamps of the first and last second of `date` in `tz`
+pub(crate) fn day_bounds<Tz: TimeZone>(date: NaiveDate, tz: &Tz) -> Result<(i64, i64)> {
+    let midnight = |date: NaiveDate| {
+        // Midnight may be skipped or repeated on DST changes
+        tz.from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest()
+            .map(|start| start.timestamp())
+    };
+    let start = midnight(date);
+    let end = date.checked_add_days(Days::new(1)).and_then(midnight);
+    match (start, end) {
+        (Some(start), Some(end)) => Ok((start, end - 1)),
+        _ => Err(Error::Validation(format!("{} has no local midnight", date))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+    use chrono::FixedOffset;
+
+    #[tokio::test]
+    async fn history_queries() {
+        // 2024-01-01 00:00 at UTC-5
+        let midnight = 1704085200;
+        let client = Client::in_memory();
+        let league_points = client.league_points(GameType::League);
+        for timestamp in [
+            midnight - 100,
+            midnight + 3600,
+            midnight + 7200,
+            midnight + 90000,
+        ] {
+            league_points
+                .add()
+                .puuid("puuid")
+                .timestamp(timestamp)
+                .tier("GOLD")
+                .rank("II")
+                .league_points(50)
+                .wins(10)
+                .losses(5)
+                .send()
+                .await
+                .unwrap();
+        }
+        let timestamp =
+            |snapshot: Option<LpItem>| snapshot.map(|snapshot| snapshot.timestamp().timestamp());
+
+        let latest = league_points.latest("puuid").send().await.unwrap();
+        assert_eq!(timestamp(latest), Some(midnight + 90000));
+        let before = league_points
+            .at_or_before("puuid", midnight + 7201)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(timestamp(before), Some(midnight + 7200));
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let first = league_points
+            .first_of_day("puuid", date, &tz)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(timestamp(first), Some(midnight + 3600));
+        let history = league_points
+            .history("puuid", ..=midnight + 7200)
+            .send()
+            .await
+            .unwrap();
+        let history: Vec<_> = history
+            .iter()
+            .map(|snapshot| snapshot.timestamp().timestamp())
+            .collect();
+        assert_eq!(
+            history,
+            vec![midnight - 100, midnight + 3600, midnight + 7200]
+        );
+        assert!(league_points
+            .latest("unknown")
+            .send()
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,14 +1,24 @@
-use crate::{ident, GameType, Handle, ItemModel};
+use crate::error::Result;
+use crate::{check_queue, ident, GameType, Handle, ItemModel, Queue};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use riot_sdk::PlayerRank;
 use serde::{Deserialize, Serialize};
+use std::ops::RangeBounds;
 use std::sync::Arc;
 
 pub mod add;
+pub mod batch;
 pub mod between;
+pub mod compact;
+pub mod daily;
 pub mod get;
+pub mod history;
+pub mod retention;
+pub mod summary;
 /// Module responsible for handling the league points entries per summoner
 ///
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LpItem {
     #[serde(flatten)]
@@ -19,11 +29,21 @@ pub struct LpItem {
     pub league_points: i32,
     pub wins: i64,
     pub losses: i64,
+    /// The ranked split the snapshot was taken in, see [`crate::season::Split::id`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split: Option<String>,
+    /// When the snapshot expires, the TTL attribute of the DynamoDB table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    /// Partition key of the `byDay` index, see [`day_key`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day: Option<String>,
 }
 impl LpItem {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        game_type: GameType,
-        summoner_id: &str,
+        queue: Queue,
+        puuid: &str,
         timestamp: i64,
         tier: &str,
         rank: &str,
@@ -31,20 +51,190 @@ impl LpItem {
         wins: i64,
         losses: i64,
     ) -> Self {
-        let ident = ident(game_type);
         Self {
             item: ItemModel {
-                id: summoner_id.to_string(),
-                sk: format!("#{}{}", ident, timestamp),
+                id: puuid.to_string(),
+                sk: lp_sk(queue, timestamp),
             },
             tier: tier.to_string(),
             rank: rank.to_string(),
             league_points,
             wins,
             losses,
+            split: None,
+            expires_at: None,
+            day: day_key(queue.game_type(), timestamp),
         }
     }
 }
+impl LpItem {
+    /// The PUUID of the player
+    pub fn puuid(&self) -> &str {
+        &self.item.id
+    }
+    /// The queue of the snapshot, `None` when the sort key is not a snapshot sort key
+    pub fn queue(&self) -> Option<Queue> {
+        parse_lp_sk(&self.item.sk).map(|(queue, _)| queue)
+    }
+    pub fn game_type(&self) -> Option<GameType> {
+        self.queue().map(Queue::game_type)
+    }
+    /// The time the snapshot was taken at
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        let timestamp = parse_lp_sk(&self.item.sk).map(|(_, timestamp)| timestamp);
+        timestamp
+            .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single())
+            .unwrap_or_default()
+    }
+    /// The rank of the player, `None` for the rated tiers of Hyper Roll
+    pub fn player_rank(&self) -> Option<PlayerRank> {
+        PlayerRank::try_parse_str(&self.tier, &self.rank, self.league_points)
+    }
+    /// Comparable value of the rank, see [`rank_points`]
+    pub fn rank_points(&self) -> i32 {
+        rank_points(&self.tier, &self.rank, self.league_points)
+    }
+    /// Whether both snapshots hold the same tier, rank, league points, wins and losses
+    pub fn same_standing(&self, other: &LpItem) -> bool {
+        self.tier == other.tier
+            && self.rank == other.rank
+            && self.league_points == other.league_points
+            && self.wins == other.wins
+            && self.losses == other.losses
+    }
+    pub fn games_played(&self) -> i64 {
+        self.wins + self.losses
+    }
+    /// The games played between `previous` and this snapshot
+    pub fn games_played_since(&self, previous: &LpItem) -> i64 {
+        self.games_played() - previous.games_played()
+    }
+}
+
+/// The latest standing of a player in a queue, stored next to the snapshots written with
+/// [`add::AddBuilder::skip_unchanged_since`] so those writes can be conditioned on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LatestStanding {
+    #[serde(flatten)]
+    pub item: ItemModel,
+    pub tier: String,
+    pub rank: String,
+    pub league_points: i32,
+    pub wins: i64,
+    pub losses: i64,
+    /// When the snapshot holding this standing was taken
+    pub timestamp: i64,
+    /// Expires along with the snapshot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+impl LatestStanding {
+    /// The standing of a snapshot, `None` when the sort key is not a snapshot sort key
+    pub fn of(item: &LpItem) -> Option<Self> {
+        let (queue, timestamp) = parse_lp_sk(&item.item.sk)?;
+        Some(Self {
+            item: ItemModel {
+                id: item.item.id.clone(),
+                sk: latest_standing_sk(queue),
+            },
+            tier: item.tier.clone(),
+            rank: item.rank.clone(),
+            league_points: item.league_points,
+            wins: item.wins,
+            losses: item.losses,
+            timestamp,
+            expires_at: item.expires_at,
+        })
+    }
+    /// Whether `next` holds the same standing, and this standing was taken since `since`
+    pub fn unchanged(&self, next: &LatestStanding, since: i64) -> bool {
+        self.timestamp >= since
+            && self.tier == next.tier
+            && self.rank == next.rank
+            && self.league_points == next.league_points
+            && self.wins == next.wins
+            && self.losses == next.losses
+    }
+}
+
+/// Sort key of the [`LatestStanding`] of a player in a queue, it does not start with `#` so it
+/// never sorts among the snapshots
+pub(crate) fn latest_standing_sk(queue: Queue) -> String {
+    format!("STANDING{}", lp_sk_prefix(queue))
+}
+
+/// Comparable value of a rank, the rating for the rated tiers of Hyper Roll
+pub(crate) fn rank_points(tier: &str, rank: &str, league_points: i32) -> i32 {
+    PlayerRank::try_parse_str(tier, rank, league_points)
+        .map(|rank| rank.to_points())
+        .unwrap_or(league_points)
+}
+
+/// Tag a snapshot with its day, and with the split it was taken in and its expiry when the
+/// client has a season calendar and a retention policy
+pub(crate) fn tag_snapshot(handle: &Handle, item: &mut LpItem) {
+    let timestamp = item.timestamp().timestamp();
+    if item.split.is_none() {
+        item.split = item
+            .game_type()
+            .and_then(|game_type| handle.seasons.split_at(game_type, timestamp))
+            .map(|split| split.id());
+    }
+    if item.expires_at.is_none() {
+        item.expires_at = handle
+            .retention
+            .and_then(|policy| policy.expires_at(timestamp));
+    }
+    if item.day.is_none() {
+        item.day = item
+            .game_type()
+            .and_then(|game_type| day_key(game_type, timestamp));
+    }
+}
+
+/// Partition key of the `byDay` index, grouping the snapshots of every player by game and UTC
+/// day as `<game>#DAY#<yyyy-mm-dd>`
+pub(crate) fn day_key(game_type: GameType, timestamp: i64) -> Option<String> {
+    let date = Utc.timestamp_opt(timestamp, 0).single()?.date_naive();
+    Some(format!("{}DAY#{}", ident(game_type), date))
+}
+
+/// Sort key of a snapshot
+///
+/// The default queue of a game keeps the `#<game>#<timestamp>` key of the snapshots stored
+/// before snapshots had a queue, the other queues are keyed `#<game>#<queue>#<timestamp>`. Queue
+/// names start with a letter so they sort after every timestamp of the default queue.
+pub(crate) fn lp_sk(queue: Queue, timestamp: impl std::fmt::Display) -> String {
+    format!("{}{}", lp_sk_prefix(queue), timestamp)
+}
+
+/// Parse the queue and timestamp of a sort key built by [`lp_sk`]
+fn parse_lp_sk(sk: &str) -> Option<(Queue, i64)> {
+    let split = sk.rfind('#')? + 1;
+    let timestamp = sk[split..].parse().ok()?;
+    let queue = Queue::ALL
+        .into_iter()
+        .find(|queue| lp_sk_prefix(*queue) == sk[..split])?;
+    Some((queue, timestamp))
+}
+
+pub(crate) fn lp_sk_prefix(queue: Queue) -> String {
+    let ident = ident(queue.game_type());
+    match queue {
+        Queue::Solo | Queue::TftRanked => format!("#{}", ident),
+        Queue::Flex => format!("#{}FLEX#", ident),
+        Queue::HyperRoll => format!("#{}TURBO#", ident),
+        Queue::DoubleUp => format!("#{}DOUBLE_UP#", ident),
+    }
+}
+
+/// The queue of a builder, the default queue of the game when unset
+pub(crate) fn resolve_queue(game_type: GameType, queue: Option<Queue>) -> Result<Queue> {
+    let queue = queue.unwrap_or(Queue::default_for(game_type));
+    check_queue(game_type, queue)?;
+    Ok(queue)
+}
 pub struct LeaguePointClient {
     handle: Arc<Handle>,
     game_type: GameType,
@@ -57,10 +247,143 @@ impl LeaguePointClient {
     pub fn add(&self) -> add::AddBuilder {
         add::AddBuilder::new(self.handle.clone(), self.game_type)
     }
+    /// Write many snapshots at once, they must all be snapshots of this client's game
+    pub fn add_many(&self, items: Vec<LpItem>) -> batch::AddManyBuilder {
+        batch::AddManyBuilder::new(self.handle.clone(), self.game_type, items)
+    }
+    pub fn get_many(&self) -> batch::GetManyBuilder {
+        batch::GetManyBuilder::new(self.handle.clone(), self.game_type)
+    }
     pub fn get(&self) -> self::get::GetBuilder {
         self::get::GetBuilder::new(self.handle.clone(), self.game_type)
     }
     pub fn get_between(&self) -> self::between::GetBetweenBuilder {
         self::between::GetBetweenBuilder::new(self.handle.clone(), self.game_type)
     }
+    pub fn compact(&self) -> self::compact::CompactBuilder {
+        self::compact::CompactBuilder::new(self.handle.clone(), self.game_type)
+    }
+    /// Store the end of split summary of a player, see [`crate::Client::with_seasons`]
+    pub fn summarize_split(&self) -> summary::SummarizeSplitBuilder {
+        summary::SummarizeSplitBuilder::new(self.handle.clone(), self.game_type)
+    }
+    /// Remove the snapshots of a player outside of the retention policy, see
+    /// [`crate::Client::with_retention`]
+    pub fn apply_retention(&self) -> retention::ApplyRetentionBuilder {
+        retention::ApplyRetentionBuilder::new(self.handle.clone(), self.game_type)
+    }
+    pub fn split_summaries(&self) -> summary::SplitSummariesBuilder {
+        summary::SplitSummariesBuilder::new(self.handle.clone(), self.game_type)
+    }
+    /// The most recent snapshot of a player
+    pub fn latest(&self, puuid: &str) -> history::FindSnapshotBuilder {
+        history::FindSnapshotBuilder::new(
+            self.handle.clone(),
+            self.game_type,
+            puuid,
+            Ok((0, i64::MAX)),
+            false,
+        )
+    }
+    /// The last snapshot of a player taken at or before `timestamp`
+    pub fn at_or_before(&self, puuid: &str, timestamp: i64) -> history::FindSnapshotBuilder {
+        history::FindSnapshotBuilder::new(
+            self.handle.clone(),
+            self.game_type,
+            puuid,
+            Ok((0, timestamp)),
+            false,
+        )
+    }
+    /// The snapshots of a player taken in `range`, oldest first
+    pub fn history(&self, puuid: &str, range: impl RangeBounds<i64>) -> history::HistoryBuilder {
+        history::HistoryBuilder::new(self.handle.clone(), self.game_type, puuid, range)
+    }
+    /// The snapshots of every player taken on `date` in the time zone `tz`
+    pub fn day<Tz: TimeZone>(&self, date: NaiveDate, tz: &Tz) -> daily::DaySnapshotsBuilder {
+        daily::DaySnapshotsBuilder::new(
+            self.handle.clone(),
+            self.game_type,
+            history::day_bounds(date, tz),
+        )
+    }
+    /// The first and last snapshots of every player on `date` in the time zone `tz`
+    pub fn daily_standings<Tz: TimeZone>(
+        &self,
+        date: NaiveDate,
+        tz: &Tz,
+    ) -> daily::DailyStandingsBuilder {
+        daily::DailyStandingsBuilder::new(
+            self.handle.clone(),
+            self.game_type,
+            history::day_bounds(date, tz),
+        )
+    }
+    /// The first snapshot of a player taken on `date` in the time zone `tz`
+    pub fn first_of_day<Tz: TimeZone>(
+        &self,
+        puuid: &str,
+        date: NaiveDate,
+        tz: &Tz,
+    ) -> history::FindSnapshotBuilder {
+        history::FindSnapshotBuilder::new(
+            self.handle.clone(),
+            self.game_type,
+            puuid,
+            history::day_bounds(date, tz),
+            true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+
+    #[tokio::test]
+    async fn typed_snapshot() {
+        let client = Client::in_memory();
+        let league_points = client.league_points(GameType::Tft);
+        for (queue, tier, rank, wins) in [
+            (Queue::TftRanked, "GOLD", "II", 10),
+            (Queue::TftRanked, "GOLD", "I", 13),
+            (Queue::HyperRoll, "BLUE", "", 5),
+        ] {
+            league_points
+                .add()
+                .puuid("puuid")
+                .queue(queue)
+                .timestamp(1704085200 + wins)
+                .tier(tier)
+                .rank(rank)
+                .league_points(50)
+                .wins(wins)
+                .losses(5)
+                .send()
+                .await
+                .unwrap();
+        }
+        let ranked = league_points.history("puuid", ..).send().await.unwrap();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[1].puuid(), "puuid");
+        assert_eq!(ranked[1].queue(), Some(Queue::TftRanked));
+        assert_eq!(ranked[1].game_type(), Some(GameType::Tft));
+        assert_eq!(ranked[1].timestamp().timestamp(), 1704085213);
+        assert_eq!(ranked[1].games_played_since(&ranked[0]), 3);
+        assert_eq!(
+            ranked[1].player_rank().unwrap().formatted_rank(),
+            "**Gold I** 50 LP"
+        );
+
+        let hyper_roll = league_points
+            .latest("puuid")
+            .queue(Queue::HyperRoll)
+            .send()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(hyper_roll.queue(), Some(Queue::HyperRoll));
+        assert!(hyper_roll.player_rank().is_none());
+    }
 }
@@ -0,0 +1,280 @@
+use super::between::GetBetweenBuilder;
+use super::{resolve_queue, LpItem};
+use crate::error::{Error, Result};
+use crate::{GameType, Handle, Queue};
+use chrono::{TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const DAY: i64 = 24 * 60 * 60;
+const WEEK: i64 = 7 * DAY;
+
+/// How long snapshots are kept, and at which resolution
+///
+/// Every snapshot is kept for `full_resolution_days`, then only the first snapshot of each day
+/// until `daily_days`, then only the first of each week starting on Monday. Days are those of the
+/// time zone given to [`ApplyRetentionBuilder::timezone`]. Snapshots older than `max_age_days`
+/// expire through the `expiresAt` TTL attribute of the DynamoDB table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    pub full_resolution_days: i64,
+    pub daily_days: i64,
+    /// `None` keeps the weekly snapshots forever
+    #[serde(default)]
+    pub max_age_days: Option<i64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            full_resolution_days: 30,
+            daily_days: 365,
+            max_age_days: None,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// The policy configured as JSON in the `RETENTION_POLICY` environment variable, `None` when
+    /// it is not set
+    pub fn from_env() -> Result<Option<Self>> {
+        match std::env::var("RETENTION_POLICY") {
+            Ok(json) => serde_json::from_str(&json).map(Some).map_err(|e| {
+                Error::Validation(format!("RETENTION_POLICY is not a retention policy: {}", e))
+            }),
+            Err(_) => Ok(None),
+        }
+    }
+    pub(crate) fn validate(&self) -> Result<()> {
+        let max_age = self.max_age_days.unwrap_or(i64::MAX);
+        match 0 <= self.full_resolution_days
+            && self.full_resolution_days <= self.daily_days
+            && self.daily_days <= max_age
+        {
+            true => Ok(()),
+            false => Err(Error::Validation(format!(
+                "Retention periods must be increasing: {:?}",
+                self
+            ))),
+        }
+    }
+    /// The expiry of a snapshot taken at `timestamp`, `None` when snapshots do not expire
+    pub fn expires_at(&self, timestamp: i64) -> Option<i64> {
+        self.max_age_days.map(|days| timestamp + days * DAY)
+    }
+    /// Whether each snapshot is kept at `now`, the snapshots being sorted oldest first
+    ///
+    /// The latest snapshot is always kept so the current rank of the player is never lost.
+    fn retained<Tz: TimeZone>(&self, now: i64, snapshots: &[LpItem], tz: &Tz) -> Vec<bool> {
+        let mut buckets = HashSet::new();
+        let last = snapshots.len().saturating_sub(1);
+        snapshots
+            .iter()
+            .enumerate()
+            .map(|(i, snapshot)| {
+                let timestamp = snapshot.timestamp().timestamp();
+                let age = now - timestamp;
+                if i == last || age < self.full_resolution_days * DAY {
+                    return true;
+                }
+                if self.max_age_days.is_some_and(|days| age >= days * DAY) {
+                    return false;
+                }
+                // The first snapshot of each day or week
+                let date = snapshot.timestamp().with_timezone(tz).date_naive();
+                let bucket = match age < self.daily_days * DAY {
+                    true => (DAY, date),
+                    false => (WEEK, date.week(Weekday::Mon).first_day()),
+                };
+                buckets.insert(bucket)
+            })
+            .collect()
+    }
+}
+
+/// Remove the snapshots of a player outside of the client's retention policy, see
+/// [`crate::Client::with_retention`]
+///
+/// Also sets the expiry of the kept snapshots written before the policy had a maximum age.
+pub struct ApplyRetentionBuilder<Tz: TimeZone = Utc> {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
+    /// Defaults to now
+    now: Option<i64>,
+    /// The time zone of the days, defaults to UTC
+    tz: Tz,
+    game_type: GameType,
+}
+
+impl ApplyRetentionBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
+        Self {
+            handle,
+            puuid: None,
+            queue: None,
+            now: None,
+            tz: Utc,
+            game_type,
+        }
+    }
+}
+
+impl<Tz: TimeZone> ApplyRetentionBuilder<Tz> {
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+    pub fn now(mut self, timestamp: i64) -> Self {
+        self.now = Some(timestamp);
+        self
+    }
+    /// The time zone of the lp-serv schedule, the first snapshot of each of its days is kept
+    pub fn timezone<T: TimeZone>(self, tz: &T) -> ApplyRetentionBuilder<T> {
+        ApplyRetentionBuilder {
+            handle: self.handle,
+            puuid: self.puuid,
+            queue: self.queue,
+            now: self.now,
+            tz: tz.clone(),
+            game_type: self.game_type,
+        }
+    }
+    /// Returns the number of removed snapshots
+    pub async fn send(self) -> Result<usize> {
+        let policy = self
+            .handle
+            .retention
+            .ok_or_else(|| Error::Validation("No retention policy configured".to_string()))?;
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        let queue = resolve_queue(self.game_type, self.queue)?;
+        let now = self.now.unwrap_or_else(|| Utc::now().timestamp());
+
+        let snapshots = GetBetweenBuilder::new(self.handle.clone(), self.game_type)
+            .puuid(&puuid)
+            .queue(queue)
+            .start_time(0)
+            .end_time(now)
+            .send()
+            .await?
+            .unwrap_or_default();
+        let retained = policy.retained(now, &snapshots, &self.tz);
+
+        let mut removed = Vec::new();
+        let mut expiring = Vec::new();
+        for (mut snapshot, retained) in snapshots.into_iter().zip(retained) {
+            if !retained {
+                removed.push(snapshot.item);
+                continue;
+            }
+            let expires_at = policy.expires_at(snapshot.timestamp().timestamp());
+            if expires_at.is_some() && snapshot.expires_at.is_none() {
+                snapshot.expires_at = expires_at;
+                expiring.push(snapshot);
+            }
+        }
+        if !removed.is_empty() {
+            self.handle.store.remove_items(&removed).await?;
+        }
+        if !expiring.is_empty() {
+            self.handle.store.add_many(expiring).await?;
+        }
+        Ok(removed.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+    use chrono::FixedOffset;
+
+    #[tokio::test]
+    async fn retention_policy() {
+        const DAY: i64 = 86400;
+        // Midnight UTC, so that the snapshots of a day share its bucket
+        const NOW: i64 = 1704672000 + 100 * DAY;
+        let policy = RetentionPolicy {
+            full_resolution_days: 7,
+            daily_days: 30,
+            max_age_days: Some(90),
+        };
+        let client = Client::in_memory().with_retention(policy).unwrap();
+        let league_points = client.league_points(GameType::League);
+        // Two snapshots a day for 100 days
+        let snapshots = (0..100)
+            .flat_map(|day| [NOW - day * DAY - 60, NOW - day * DAY - 12 * 3600])
+            .map(|timestamp| LpItem::new(Queue::Solo, "puuid", timestamp, "GOLD", "I", 1, 1, 1))
+            .collect();
+        league_points.add_many(snapshots).send().await.unwrap();
+        let history = league_points.history("puuid", ..).send().await.unwrap();
+        assert_eq!(history.len(), 200);
+        assert!(history
+            .iter()
+            .all(|lp| lp.expires_at == Some(lp.timestamp().timestamp() + 90 * DAY)));
+
+        let removed = league_points
+            .apply_retention()
+            .puuid("puuid")
+            .now(NOW)
+            .send()
+            .await
+            .unwrap();
+        let history = league_points.history("puuid", ..).send().await.unwrap();
+        assert_eq!(removed, 200 - history.len());
+        let age = |lp: &LpItem| (NOW - lp.timestamp().timestamp()) / DAY;
+        let count = |range: std::ops::Range<i64>| {
+            history.iter().filter(|lp| range.contains(&age(lp))).count()
+        };
+        // Every snapshot of the last week, one a day until 30 days, one a week until 90 days
+        assert_eq!(count(0..7), 14);
+        assert_eq!(count(7..30), 23);
+        assert!((8..=10).contains(&count(30..90)));
+        assert_eq!(count(90..100), 0);
+
+        // Snapshots at 23:59 and 00:00 in UTC-5 for 20 days, 2024-01-01 00:00 being the first
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let midnight = |day: i64| 1704085200 + day * DAY;
+        let snapshots = (0..20)
+            .flat_map(|day| [midnight(day) - 60, midnight(day)])
+            .map(|timestamp| LpItem::new(Queue::Solo, "night", timestamp, "GOLD", "I", 1, 1, 1))
+            .collect();
+        league_points.add_many(snapshots).send().await.unwrap();
+        let removed = league_points
+            .apply_retention()
+            .puuid("night")
+            .now(midnight(20) + 7 * DAY)
+            .timezone(&tz)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(removed, 19);
+        // The first snapshot of each local day is the one taken at midnight, but for the first
+        // snapshot which is alone on its day
+        let history = league_points.history("night", ..).send().await.unwrap();
+        assert!(history[1..]
+            .iter()
+            .all(|lp| (lp.timestamp().timestamp() - midnight(0)) % DAY == 0));
+
+        let res = Client::in_memory().with_retention(RetentionPolicy {
+            full_resolution_days: 30,
+            daily_days: 7,
+            max_age_days: None,
+        });
+        assert!(matches!(res, Err(Error::Validation(_))));
+        let res = Client::in_memory()
+            .league_points(GameType::League)
+            .apply_retention()
+            .puuid("puuid")
+            .send()
+            .await;
+        assert!(matches!(res, Err(Error::Validation(_))));
+    }
+}
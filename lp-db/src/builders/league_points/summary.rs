@@ -0,0 +1,279 @@
+use super::between::GetBetweenBuilder;
+use super::{rank_points, resolve_queue, LpItem};
+use crate::error::{Error, Result};
+use crate::{GameType, Handle, ItemModel, Queue};
+use riot_sdk::PlayerRank;
+use serde::{Deserialize, Serialize};
+
+/// The end of split standing of a player in a queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitSummary {
+    #[serde(flatten)]
+    /// The item partition key
+    pub item: ItemModel,
+    /// See [`crate::season::Split::id`]
+    pub split: String,
+    pub queue: Queue,
+    /// The rank of the last snapshot of the split
+    pub final_rank: Standing,
+    /// The highest rank of the split
+    pub peak: Standing,
+    pub wins: i64,
+    pub losses: i64,
+}
+
+/// The rank of a player at the time of a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Standing {
+    pub tier: String,
+    pub rank: String,
+    pub league_points: i32,
+    pub timestamp: i64,
+}
+
+impl From<&LpItem> for Standing {
+    fn from(snapshot: &LpItem) -> Self {
+        Self {
+            tier: snapshot.tier.clone(),
+            rank: snapshot.rank.clone(),
+            league_points: snapshot.league_points,
+            timestamp: snapshot.timestamp().timestamp(),
+        }
+    }
+}
+
+impl Standing {
+    /// The rank of the player, `None` for the rated tiers of Hyper Roll
+    pub fn player_rank(&self) -> Option<PlayerRank> {
+        PlayerRank::try_parse_str(&self.tier, &self.rank, self.league_points)
+    }
+    /// Comparable value of the rank, see [`LpItem::rank_points`]
+    pub fn rank_points(&self) -> i32 {
+        rank_points(&self.tier, &self.rank, self.league_points)
+    }
+}
+
+impl SplitSummary {
+    /// The PUUID of the player
+    pub fn puuid(&self) -> &str {
+        &self.item.id
+    }
+    pub fn games_played(&self) -> i64 {
+        self.wins + self.losses
+    }
+}
+
+/// Key of a split summary, stored in the partition of the player along with their snapshots
+fn summary_key(puuid: &str, queue: Queue, split: &str) -> ItemModel {
+    ItemModel {
+        id: puuid.to_string(),
+        sk: format!("SUMMARY#{}#{}", queue.riot_queue_type(), split),
+    }
+}
+
+/// Store the summary of a player's split, from the snapshots taken during the split
+pub struct SummarizeSplitBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
+    /// See [`crate::season::Split::id`]
+    split: Option<String>,
+    game_type: GameType,
+}
+
+impl SummarizeSplitBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
+        Self {
+            handle,
+            puuid: None,
+            queue: None,
+            split: None,
+            game_type,
+        }
+    }
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+    /// A split of the client's season calendar
+    pub fn split(mut self, split: &str) -> Self {
+        self.split = Some(split.to_string());
+        self
+    }
+    /// Returns `None`, without storing anything, when the player has no snapshot in the split
+    pub async fn send(self) -> Result<Option<SplitSummary>> {
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        let split_id = self.split.ok_or_else(|| Error::missing("split"))?;
+        let queue = resolve_queue(self.game_type, self.queue)?;
+        let seasons = &self.handle.seasons;
+        let split = seasons
+            .find(self.game_type, &split_id)
+            .ok_or_else(|| Error::Validation(format!("Unknown split {}", split_id)))?;
+        let (start_time, end_time) = seasons.bounds(self.game_type, split);
+
+        let snapshots = GetBetweenBuilder::new(self.handle.clone(), self.game_type)
+            .puuid(&puuid)
+            .queue(queue)
+            .start_time(start_time)
+            .end_time(end_time)
+            .send()
+            .await?
+            .unwrap_or_default();
+        let (Some(last), Some(peak)) = (
+            snapshots.last(),
+            // The first snapshot reaching the peak
+            snapshots
+                .iter()
+                .rev()
+                .max_by_key(|snapshot| snapshot.rank_points()),
+        ) else {
+            return Ok(None);
+        };
+        let summary = SplitSummary {
+            item: summary_key(&puuid, queue, &split_id),
+            split: split_id,
+            queue,
+            final_rank: last.into(),
+            peak: peak.into(),
+            wins: last.wins,
+            losses: last.losses,
+        };
+        let item = serde_json::to_value(&summary)?;
+        self.handle.store.put_items(vec![item]).await?;
+        Ok(Some(summary))
+    }
+}
+
+/// Get the stored split summaries of a player
+pub struct SplitSummariesBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+    /// The ranked queue, the default queue of the game when unset
+    queue: Option<Queue>,
+    game_type: GameType,
+}
+
+impl SplitSummariesBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
+        Self {
+            handle,
+            puuid: None,
+            queue: None,
+            game_type,
+        }
+    }
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub fn queue(mut self, queue: Queue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+    /// The summaries of the splits of the client's season calendar, oldest first
+    pub async fn send(self) -> Result<Vec<SplitSummary>> {
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        let queue = resolve_queue(self.game_type, self.queue)?;
+        let mut splits = self.handle.seasons.splits(self.game_type).to_vec();
+        if splits.is_empty() {
+            return Ok(Vec::new());
+        }
+        splits.sort_by_key(|split| split.start);
+        let keys = splits
+            .iter()
+            .map(|split| summary_key(&puuid, queue, &split.id()))
+            .collect::<Vec<_>>();
+        let items = self.handle.store.get_items(&keys).await?;
+        let mut summaries = items
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<std::result::Result<Vec<SplitSummary>, _>>()?;
+        summaries
+            .sort_by_key(|summary| splits.iter().position(|split| split.id() == summary.split));
+        Ok(summaries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::season::SeasonCalendar;
+    use crate::Client;
+
+    #[tokio::test]
+    async fn split_summaries() {
+        const T: i64 = 1704085200;
+        const RESET: i64 = T + 10 * 86400;
+        let seasons = SeasonCalendar::new()
+            .split(GameType::League, "2024", 2, RESET)
+            .split(GameType::League, "2024", 1, T - 86400);
+        let client = Client::in_memory().with_seasons(seasons);
+        let league_points = client.league_points(GameType::League);
+        let add = |timestamp, tier: &str, rank: &str, league_points, wins| {
+            client
+                .league_points(GameType::League)
+                .add()
+                .puuid("puuid")
+                .timestamp(timestamp)
+                .tier(tier)
+                .rank(rank)
+                .league_points(league_points)
+                .wins(wins)
+                .losses(10)
+        };
+        add(T, "GOLD", "II", 50, 10).send().await.unwrap();
+        add(T + 86400, "PLATINUM", "IV", 10, 15)
+            .send()
+            .await
+            .unwrap();
+        add(T + 2 * 86400, "GOLD", "I", 90, 20)
+            .send()
+            .await
+            .unwrap();
+        add(RESET + 3600, "SILVER", "I", 0, 1).send().await.unwrap();
+
+        let summary = league_points
+            .summarize_split()
+            .puuid("puuid")
+            .split("2024-S1")
+            .send()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(summary.final_rank.tier, "GOLD");
+        assert_eq!(summary.peak.tier, "PLATINUM");
+        assert_eq!(summary.games_played(), 30);
+        league_points
+            .summarize_split()
+            .puuid("puuid")
+            .split("2024-S2")
+            .send()
+            .await
+            .unwrap();
+        let summaries = league_points
+            .split_summaries()
+            .puuid("puuid")
+            .send()
+            .await
+            .unwrap();
+        let splits: Vec<_> = summaries.iter().map(|s| s.split.as_str()).collect();
+        assert_eq!(splits, vec!["2024-S1", "2024-S2"]);
+
+        let res = league_points
+            .summarize_split()
+            .puuid("puuid")
+            .split("2023-S3")
+            .send()
+            .await;
+        assert!(matches!(res, Err(Error::Validation(_))));
+    }
+}
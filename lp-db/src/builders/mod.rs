@@ -1,2 +1,4 @@
+pub mod audit;
 pub mod league_points;
+pub mod profile;
 pub mod tracking;
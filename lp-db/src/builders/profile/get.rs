@@ -0,0 +1,73 @@
+use super::{profile_key, PlayerProfile};
+use crate::error::{Error, Result};
+use crate::Handle;
+use std::collections::BTreeSet;
+
+pub struct GetProfileBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+}
+
+impl GetProfileBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>) -> Self {
+        Self {
+            handle,
+            puuid: None,
+        }
+    }
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub async fn send(self) -> Result<Option<PlayerProfile>> {
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        get_profile(&self.handle, &puuid).await
+    }
+}
+
+pub struct GetManyProfilesBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUIDs of the players
+    puuids: Vec<String>,
+}
+
+impl GetManyProfilesBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>) -> Self {
+        Self {
+            handle,
+            puuids: Vec::new(),
+        }
+    }
+    pub fn puuids(mut self, puuids: &[String]) -> Self {
+        self.puuids = puuids.to_vec();
+        self
+    }
+    /// Get the profiles in any order, players without a profile are skipped
+    pub async fn send(self) -> Result<Vec<PlayerProfile>> {
+        if self.puuids.is_empty() {
+            return Ok(Vec::new());
+        }
+        // DynamoDB rejects batches with duplicate keys
+        let keys = self
+            .puuids
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|puuid| profile_key(puuid))
+            .collect::<Vec<_>>();
+        let items = self.handle.store.get_items(&keys).await?;
+        items
+            .into_iter()
+            .map(|item| Ok(serde_json::from_value(item)?))
+            .collect()
+    }
+}
+
+pub(crate) async fn get_profile(handle: &Handle, puuid: &str) -> Result<Option<PlayerProfile>> {
+    let items = handle.store.get_items(&[profile_key(puuid)]).await?;
+    match items.into_iter().next() {
+        Some(item) => Ok(Some(serde_json::from_value(item)?)),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,283 @@
+use super::league_points::{rank_points, LpItem};
+use crate::{Handle, ItemModel, Queue};
+use riot_sdk::PlayerRank;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub mod get;
+pub mod update;
+/// Module responsible for the metadata of the players, kept across renames
+///
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerProfile {
+    #[serde(flatten)]
+    /// The item partition key
+    pub item: ItemModel,
+    /// The current Riot ID, as `<game name>#<tag line>`
+    pub riot_id: String,
+    /// The Riot IDs of the player before their renames, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_riot_ids: Vec<PreviousRiotId>,
+    /// The platform the player plays on (e.g. `na1`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// The Discord users linked to the player
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub discord_user_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preferred_queues: Vec<Queue>,
+    /// The highest rank of the player per season and queue
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub peak_ranks: Vec<PeakRank>,
+    /// Incremented by every write, writes are conditioned on the version they were made from
+    #[serde(default)]
+    pub version: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousRiotId {
+    pub riot_id: String,
+    /// When the player was renamed
+    pub renamed_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeakRank {
+    pub season: String,
+    pub queue: Queue,
+    pub tier: String,
+    pub rank: String,
+    pub league_points: i32,
+    /// When the rank was reached
+    pub timestamp: i64,
+}
+
+impl PeakRank {
+    /// The rank of the player, `None` for the rated tiers of Hyper Roll
+    pub fn player_rank(&self) -> Option<PlayerRank> {
+        PlayerRank::try_parse_str(&self.tier, &self.rank, self.league_points)
+    }
+    /// Comparable value of the rank, the rating for the rated tiers of Hyper Roll
+    fn points(&self) -> i32 {
+        rank_points(&self.tier, &self.rank, self.league_points)
+    }
+}
+
+impl PlayerProfile {
+    pub fn new(puuid: &str, riot_id: &str) -> Self {
+        Self {
+            item: profile_key(puuid),
+            riot_id: riot_id.to_string(),
+            previous_riot_ids: Vec::new(),
+            platform: None,
+            discord_user_ids: Vec::new(),
+            preferred_queues: Vec::new(),
+            peak_ranks: Vec::new(),
+            version: 0,
+        }
+    }
+    /// The PUUID of the player
+    pub fn puuid(&self) -> &str {
+        &self.item.id
+    }
+    /// The Riot ID the player had before their last rename
+    pub fn previous_riot_id(&self) -> Option<&str> {
+        self.previous_riot_ids
+            .last()
+            .map(|previous| previous.riot_id.as_str())
+    }
+    /// Change the Riot ID, keeping the current one in the name history
+    ///
+    /// Returns whether the Riot ID changed.
+    pub fn rename(&mut self, riot_id: &str, timestamp: i64) -> bool {
+        if self.riot_id == riot_id {
+            return false;
+        }
+        let previous = std::mem::replace(&mut self.riot_id, riot_id.to_string());
+        self.previous_riot_ids.push(PreviousRiotId {
+            riot_id: previous,
+            renamed_at: timestamp,
+        });
+        true
+    }
+    /// The highest rank of the player in a queue during a season
+    pub fn peak(&self, season: &str, queue: Queue) -> Option<&PeakRank> {
+        self.peak_ranks
+            .iter()
+            .find(|peak| peak.season == season && peak.queue == queue)
+    }
+    /// Keep the snapshot as the peak rank of its queue for the season if it is higher
+    ///
+    /// Returns whether the peak rank changed.
+    pub fn record_peak(&mut self, season: &str, snapshot: &LpItem) -> bool {
+        let Some(queue) = snapshot.queue() else {
+            return false;
+        };
+        let peak = PeakRank {
+            season: season.to_string(),
+            queue,
+            tier: snapshot.tier.clone(),
+            rank: snapshot.rank.clone(),
+            league_points: snapshot.league_points,
+            timestamp: snapshot.timestamp().timestamp(),
+        };
+        let current = self
+            .peak_ranks
+            .iter_mut()
+            .find(|current| current.season == season && current.queue == queue);
+        match current {
+            Some(current) if current.points() >= peak.points() => false,
+            Some(current) => {
+                *current = peak;
+                true
+            }
+            None => {
+                self.peak_ranks.push(peak);
+                true
+            }
+        }
+    }
+}
+
+/// Key of the profile, stored in the partition of the player along with their snapshots
+pub(crate) fn profile_key(puuid: &str) -> ItemModel {
+    ItemModel {
+        id: puuid.to_string(),
+        sk: "PROFILE".to_string(),
+    }
+}
+
+pub struct ProfileClient {
+    handle: Arc<Handle>,
+}
+
+impl ProfileClient {
+    pub fn new(handle: Arc<Handle>) -> Self {
+        Self { handle }
+    }
+    pub fn create(&self) -> update::CreateProfileBuilder {
+        update::CreateProfileBuilder::new(self.handle.clone())
+    }
+    pub fn get(&self) -> get::GetProfileBuilder {
+        get::GetProfileBuilder::new(self.handle.clone())
+    }
+    pub fn get_many(&self) -> get::GetManyProfilesBuilder {
+        get::GetManyProfilesBuilder::new(self.handle.clone())
+    }
+    pub fn update(&self) -> update::UpdateProfileBuilder {
+        update::UpdateProfileBuilder::new(self.handle.clone())
+    }
+    pub fn delete(&self) -> update::DeleteProfileBuilder {
+        update::DeleteProfileBuilder::new(self.handle.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::Client;
+
+    #[tokio::test]
+    async fn player_profiles() {
+        const T: i64 = 1704085200;
+        let client = Client::in_memory();
+        let profiles = client.profiles();
+        profiles
+            .create()
+            .puuid("puuid")
+            .riot_id("Old#NA1")
+            .platform("na1")
+            .send()
+            .await
+            .unwrap();
+        let res = profiles
+            .create()
+            .puuid("puuid")
+            .riot_id("Old#NA1")
+            .send()
+            .await;
+        assert!(matches!(res, Err(Error::Validation(_))));
+
+        let snapshot = |tier: &str, rank: &str, league_points, timestamp| {
+            LpItem::new(
+                Queue::Solo,
+                "puuid",
+                timestamp,
+                tier,
+                rank,
+                league_points,
+                10,
+                10,
+            )
+        };
+        profiles
+            .update()
+            .puuid("puuid")
+            .riot_id("New#NA1")
+            .timestamp(T)
+            .link_discord_user("1234")
+            .peak("2024", snapshot("GOLD", "I", 80, T))
+            .send()
+            .await
+            .unwrap();
+        let profile = profiles
+            .update()
+            .puuid("puuid")
+            .riot_id("New#NA1")
+            .peak("2024", snapshot("GOLD", "II", 90, T + 3600))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(profile.riot_id, "New#NA1");
+        assert_eq!(profile.previous_riot_id(), Some("Old#NA1"));
+        assert_eq!(profile.previous_riot_ids[0].renamed_at, T);
+        assert_eq!(profile.platform.as_deref(), Some("na1"));
+        assert_eq!(profile.discord_user_ids, vec!["1234"]);
+        let peak = profile.peak("2024", Queue::Solo).unwrap();
+        assert_eq!((peak.rank.as_str(), peak.timestamp), ("I", T));
+
+        let fetched = profiles.get().puuid("puuid").send().await.unwrap().unwrap();
+        assert_eq!(fetched.previous_riot_ids, profile.previous_riot_ids);
+        // Writes made from a stale version are rejected
+        assert_eq!(fetched.version, 3);
+        let mut stale = serde_json::to_value(&fetched).unwrap();
+        stale["version"] = serde_json::json!(3);
+        let store = &client.handle.store;
+        assert!(!store.put_versioned(stale.clone(), Some(2)).await.unwrap());
+        assert!(!store.put_versioned(stale.clone(), None).await.unwrap());
+        let concurrent = futures::future::join_all((0..4).map(|i| {
+            profiles
+                .update()
+                .puuid("puuid")
+                .link_discord_user(&i.to_string())
+                .send()
+        }))
+        .await;
+        assert!(concurrent.iter().all(Result::is_ok));
+        let fetched = profiles.get().puuid("puuid").send().await.unwrap().unwrap();
+        assert_eq!(fetched.discord_user_ids.len(), 5);
+        let many = profiles
+            .get_many()
+            .puuids(&["puuid".to_string(), "other".to_string()])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(many.len(), 1);
+
+        let res = profiles.update().puuid("other").send().await;
+        assert!(matches!(res, Err(Error::Validation(_))));
+        profiles.delete().puuid("puuid").send().await.unwrap();
+        assert!(profiles
+            .get()
+            .puuid("puuid")
+            .send()
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,231 @@
+use super::get::get_profile;
+use super::{profile_key, PlayerProfile};
+use crate::builders::league_points::LpItem;
+use crate::error::{Error, Result};
+use crate::{Handle, Queue};
+use chrono::Utc;
+
+/// Attempts left to update a profile changed by another writer since it was read
+const PUT_RETRIES: u32 = 3;
+
+pub struct CreateProfileBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+    riot_id: Option<String>,
+    platform: Option<String>,
+    discord_user_ids: Vec<String>,
+    preferred_queues: Vec<Queue>,
+}
+
+impl CreateProfileBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>) -> Self {
+        Self {
+            handle,
+            puuid: None,
+            riot_id: None,
+            platform: None,
+            discord_user_ids: Vec::new(),
+            preferred_queues: Vec::new(),
+        }
+    }
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    /// The Riot ID, as `<game name>#<tag line>`
+    pub fn riot_id(mut self, riot_id: &str) -> Self {
+        self.riot_id = Some(riot_id.to_string());
+        self
+    }
+    pub fn platform(mut self, platform: &str) -> Self {
+        self.platform = Some(platform.to_string());
+        self
+    }
+    pub fn discord_user_id(mut self, discord_user_id: &str) -> Self {
+        self.discord_user_ids.push(discord_user_id.to_string());
+        self
+    }
+    pub fn preferred_queues(mut self, queues: &[Queue]) -> Self {
+        self.preferred_queues = queues.to_vec();
+        self
+    }
+    /// Fails when the player already has a profile, use [`UpdateProfileBuilder`] to change it
+    pub async fn send(self) -> Result<PlayerProfile> {
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        let riot_id = self.riot_id.ok_or_else(|| Error::missing("riot_id"))?;
+        let mut profile = PlayerProfile::new(&puuid, &riot_id);
+        profile.platform = self.platform;
+        profile.discord_user_ids = self.discord_user_ids;
+        profile.preferred_queues = self.preferred_queues;
+        if !put_profile(&self.handle, &mut profile, None).await? {
+            return Err(Error::Validation(format!(
+                "Player {} already has a profile",
+                puuid
+            )));
+        }
+        Ok(profile)
+    }
+}
+
+pub struct UpdateProfileBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+    riot_id: Option<String>,
+    /// When the changes happened, defaults to now
+    timestamp: Option<i64>,
+    platform: Option<String>,
+    link_discord_user_ids: Vec<String>,
+    unlink_discord_user_ids: Vec<String>,
+    preferred_queues: Option<Vec<Queue>>,
+    /// Snapshots to record as peak ranks, with their season
+    peaks: Vec<(String, LpItem)>,
+}
+
+impl UpdateProfileBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>) -> Self {
+        Self {
+            handle,
+            puuid: None,
+            riot_id: None,
+            timestamp: None,
+            platform: None,
+            link_discord_user_ids: Vec::new(),
+            unlink_discord_user_ids: Vec::new(),
+            preferred_queues: None,
+            peaks: Vec::new(),
+        }
+    }
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    /// The current Riot ID, the previous one is kept in the name history when it changed
+    pub fn riot_id(mut self, riot_id: &str) -> Self {
+        self.riot_id = Some(riot_id.to_string());
+        self
+    }
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+    pub fn platform(mut self, platform: &str) -> Self {
+        self.platform = Some(platform.to_string());
+        self
+    }
+    pub fn link_discord_user(mut self, discord_user_id: &str) -> Self {
+        self.link_discord_user_ids.push(discord_user_id.to_string());
+        self
+    }
+    pub fn unlink_discord_user(mut self, discord_user_id: &str) -> Self {
+        self.unlink_discord_user_ids
+            .push(discord_user_id.to_string());
+        self
+    }
+    pub fn preferred_queues(mut self, queues: &[Queue]) -> Self {
+        self.preferred_queues = Some(queues.to_vec());
+        self
+    }
+    /// Record the snapshot as the peak rank of the season if it is higher than the current one
+    pub fn peak(mut self, season: &str, snapshot: LpItem) -> Self {
+        self.peaks.push((season.to_string(), snapshot));
+        self
+    }
+    /// Creates the profile when the player does not have one yet, which requires a Riot ID
+    ///
+    /// The changes are applied again to the stored profile when it changed since it was read.
+    pub async fn send(self) -> Result<PlayerProfile> {
+        let puuid = self
+            .puuid
+            .as_deref()
+            .ok_or_else(|| Error::missing("puuid"))?;
+        for (_, snapshot) in &self.peaks {
+            if snapshot.puuid() != puuid {
+                return Err(Error::Validation(format!(
+                    "Snapshot of {} recorded in the profile of {}",
+                    snapshot.puuid(),
+                    puuid
+                )));
+            }
+        }
+        for _ in 0..=PUT_RETRIES {
+            let stored = get_profile(&self.handle, puuid).await?;
+            let previous = stored.as_ref().map(|profile| profile.version);
+            let mut profile = self.apply(puuid, stored)?;
+            if put_profile(&self.handle, &mut profile, previous).await? {
+                return Ok(profile);
+            }
+        }
+        Err(Error::Storage(format!(
+            "The profile of {} kept changing while being updated",
+            puuid
+        )))
+    }
+    fn apply(&self, puuid: &str, stored: Option<PlayerProfile>) -> Result<PlayerProfile> {
+        let timestamp = self.timestamp.unwrap_or_else(|| Utc::now().timestamp());
+        let mut profile = match (stored, &self.riot_id) {
+            (Some(mut profile), Some(riot_id)) => {
+                profile.rename(riot_id, timestamp);
+                profile
+            }
+            (Some(profile), None) => profile,
+            (None, Some(riot_id)) => PlayerProfile::new(puuid, riot_id),
+            (None, None) => return Err(Error::missing("riot_id")),
+        };
+        if let Some(platform) = &self.platform {
+            profile.platform = Some(platform.clone());
+        }
+        for discord_user_id in &self.link_discord_user_ids {
+            if !profile.discord_user_ids.contains(discord_user_id) {
+                profile.discord_user_ids.push(discord_user_id.clone());
+            }
+        }
+        profile
+            .discord_user_ids
+            .retain(|id| !self.unlink_discord_user_ids.contains(id));
+        if let Some(queues) = &self.preferred_queues {
+            profile.preferred_queues = queues.clone();
+        }
+        for (season, snapshot) in &self.peaks {
+            profile.record_peak(season, snapshot);
+        }
+        Ok(profile)
+    }
+}
+
+pub struct DeleteProfileBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUID of the player
+    puuid: Option<String>,
+}
+
+impl DeleteProfileBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>) -> Self {
+        Self {
+            handle,
+            puuid: None,
+        }
+    }
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    /// Deleting a missing profile is not an error
+    pub async fn send(self) -> Result<()> {
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        self.handle.store.remove_items(&[profile_key(&puuid)]).await
+    }
+}
+
+/// Write the profile if the stored one is still at version `previous`, missing when `None`,
+/// returns whether it was written
+async fn put_profile(
+    handle: &Handle,
+    profile: &mut PlayerProfile,
+    previous: Option<u64>,
+) -> Result<bool> {
+    profile.version = previous.unwrap_or(0) + 1;
+    let item = serde_json::to_value(&*profile)?;
+    handle.store.put_versioned(item, previous).await
+}
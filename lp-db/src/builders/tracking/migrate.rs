@@ -0,0 +1,370 @@
+use super::{tracking_sk, TrackingItem};
+use crate::builders::league_points::LpItem;
+use crate::error::{Error, Result};
+use crate::store::{paginate, BetweenQuery};
+use crate::{GameType, Handle, ItemModel, Queue};
+use async_trait::async_trait;
+use futures::future;
+use futures::stream::TryStreamExt;
+use std::collections::HashMap;
+
+/// Move the summoners tracked before tracking was scoped by guild to a guild
+///
+/// Meant to be run once with the id of the guild the bot was used in, running it again is a
+/// no-op. The summoners keep the key they were stored by, so it has to run before
+/// [`MigrateToPuuidBuilder`] which finds the league points history through that key.
+pub struct MigrateToGuildBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The guild the existing summoners are moved to
+    guild_id: Option<String>,
+    game_type: GameType,
+}
+
+impl MigrateToGuildBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
+        Self {
+            handle,
+            guild_id: None,
+            game_type,
+        }
+    }
+    pub fn guild_id(mut self, guild_id: &str) -> Self {
+        self.guild_id = Some(guild_id.to_string());
+        self
+    }
+    /// Get the number of summoners migrated
+    pub async fn send(self) -> Result<usize> {
+        let guild_id = self.guild_id.ok_or_else(|| Error::missing("guild_id"))?;
+        let (handle, game_type) = (self.handle.clone(), self.game_type);
+        let legacy: Vec<TrackingItem> = paginate(move |start| {
+            let handle = handle.clone();
+            async move { handle.store.list_tracked(game_type, None, start).await }
+        })
+        .try_filter(|item| future::ready(item.guild_id.is_none()))
+        .try_collect()
+        .await?;
+
+        for item in &legacy {
+            let mut migrated = TrackingItem::new(
+                self.game_type,
+                &guild_id,
+                &item.puuid,
+                &item.account_id,
+                &item.summoner_name,
+                &item.queues,
+            );
+            migrated.item.sk = tracking_sk(self.game_type, item.key_id(), None);
+            self.handle.store.track(migrated).await?;
+            self.handle
+                .store
+                .untrack(self.game_type, None, item.key_id(), None)
+                .await?;
+        }
+        Ok(legacy.len())
+    }
+}
+
+/// Outcome of [`MigrateToPuuidBuilder`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PuuidMigration {
+    /// Tracking items re-keyed by PUUID
+    pub tracking: usize,
+    /// League points snapshots moved to the PUUID of their summoner
+    pub snapshots: usize,
+}
+
+/// Resolves the PUUID a tracked summoner is keyed by
+///
+/// PUUIDs are encrypted per Riot API key, so the PUUID stored on a tracking item is only valid
+/// if it was resolved with the key that reads the league points of the game.
+#[async_trait]
+pub trait PuuidResolver: Send + Sync {
+    async fn resolve(&self, item: &TrackingItem) -> Result<String>;
+}
+
+/// Re-key the tracking items and league points history by PUUID
+///
+/// Items are re-keyed when they are stored by encrypted summoner id, or when the resolver gives
+/// them another PUUID. Summoner ids are resolved through the tracking items of the guilds, so it
+/// has to run after [`MigrateToGuildBuilder`], and the history of a summoner that is no longer
+/// tracked is left as is. Running it again is a no-op.
+pub struct MigrateToPuuidBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// Only count the items to migrate
+    dry_run: bool,
+    /// The stored PUUIDs are kept when unset
+    resolver: Option<Box<dyn PuuidResolver>>,
+    game_type: GameType,
+}
+
+impl MigrateToPuuidBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
+        Self {
+            handle,
+            dry_run: false,
+            resolver: None,
+            game_type,
+        }
+    }
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+    /// Resolve the PUUIDs again, e.g. with the API key of the game
+    pub fn resolver(mut self, resolver: impl PuuidResolver + 'static) -> Self {
+        self.resolver = Some(Box::new(resolver));
+        self
+    }
+    pub async fn send(self) -> Result<PuuidMigration> {
+        let (handle, game_type) = (self.handle.clone(), self.game_type);
+        let items: Vec<TrackingItem> = paginate(move |start| {
+            let handle = handle.clone();
+            async move { handle.store.list_tracked(game_type, None, start).await }
+        })
+        .try_collect()
+        .await?;
+
+        let mut migration = PuuidMigration::default();
+        // The PUUID of every key, resolved once per summoner whatever the guilds tracking it
+        let mut puuids: HashMap<String, String> = HashMap::new();
+        for item in &items {
+            let puuid = match (puuids.get(item.key_id()), &self.resolver) {
+                (Some(puuid), _) => puuid.clone(),
+                (None, Some(resolver)) => resolver.resolve(item).await?,
+                (None, None) => item.puuid.clone(),
+            };
+            puuids.insert(item.key_id().to_string(), puuid.clone());
+            // Items stored before tracking was scoped by guild are re-keyed when moved to a guild
+            let Some(guild_id) = &item.guild_id else {
+                continue;
+            };
+            if item.key_id() == puuid && item.puuid == puuid {
+                continue;
+            }
+            migration.tracking += 1;
+            if self.dry_run {
+                continue;
+            }
+            let migrated = TrackingItem::new(
+                self.game_type,
+                guild_id,
+                &puuid,
+                &item.account_id,
+                &item.summoner_name,
+                &item.queues,
+            );
+            self.handle.store.track(migrated).await?;
+            if item.key_id() != puuid {
+                self.handle
+                    .store
+                    .untrack(self.game_type, Some(guild_id), item.key_id(), None)
+                    .await?;
+            }
+        }
+
+        for (key_id, puuid) in puuids {
+            if key_id == puuid {
+                continue;
+            }
+            for queue in Queue::ALL
+                .into_iter()
+                .filter(|queue| queue.game_type() == self.game_type)
+            {
+                // Sort keys are compared as strings, every epoch timestamp sorts between these
+                let query = BetweenQuery {
+                    queue,
+                    puuid: key_id.clone(),
+                    start_time: 0,
+                    end_time: i64::MAX,
+                    limit: None,
+                    scan_forward: true,
+                };
+                let handle = self.handle.clone();
+                let snapshots: Vec<LpItem> = paginate(move |start| {
+                    let handle = handle.clone();
+                    let query = query.clone();
+                    async move { handle.store.get_between(&query, start).await }
+                })
+                .try_collect()
+                .await?;
+
+                migration.snapshots += snapshots.len();
+                if self.dry_run {
+                    continue;
+                }
+                for snapshot in snapshots {
+                    let timestamp = snapshot.timestamp().timestamp();
+                    let migrated = LpItem {
+                        item: ItemModel {
+                            id: puuid.clone(),
+                            sk: snapshot.item.sk.clone(),
+                        },
+                        ..snapshot
+                    };
+                    self.handle.store.add(migrated).await?;
+                    self.handle.store.remove(queue, &key_id, timestamp).await?;
+                }
+            }
+        }
+        Ok(migration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+    use serde_json::json;
+
+    /// Store a summoner tracked before tracking was scoped by guild and keyed by PUUID, with
+    /// two snapshots
+    async fn legacy_client() -> Client {
+        let client = Client::in_memory();
+        let legacy = json!({
+            "id": "TRACKING",
+            "sk": "SUMMONER#LEAGUE#summoner",
+            "puuid": "puuid",
+            "accountId": "account",
+            "summonerName": "monke",
+        });
+        client.handle.store.put_items(vec![legacy]).await.unwrap();
+        for timestamp in [100, 200] {
+            client
+                .league_points(GameType::League)
+                .add()
+                .puuid("summoner")
+                .timestamp(timestamp)
+                .tier("GOLD")
+                .rank("II")
+                .league_points(50)
+                .wins(10)
+                .losses(5)
+                .send()
+                .await
+                .unwrap();
+        }
+        client
+    }
+
+    async fn snapshots(client: &Client, game_type: GameType, puuid: &str) -> usize {
+        client
+            .league_points(game_type)
+            .get_between()
+            .puuid(puuid)
+            .start_time(0)
+            .end_time(300)
+            .send()
+            .await
+            .unwrap()
+            .map_or(0, |snapshots| snapshots.len())
+    }
+
+    #[tokio::test]
+    async fn migrate_to_guild() {
+        let client = legacy_client().await;
+        let tracking = client.tracking(GameType::League);
+
+        let migrated = tracking
+            .migrate_to_guild()
+            .guild_id("guild")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(migrated, 1);
+        let tracked = tracking.list().send().await.unwrap();
+        assert_eq!(tracked.len(), 1);
+        assert_eq!(tracked[0].guild_id.as_deref(), Some("guild"));
+        // Still keyed by summoner id until the PUUID migration
+        assert_eq!(tracked[0].key_id(), "summoner");
+    }
+    #[tokio::test]
+    async fn migrate_to_guild_then_puuid() {
+        let client = legacy_client().await;
+        let tracking = client.tracking(GameType::League);
+        tracking
+            .migrate_to_guild()
+            .guild_id("guild")
+            .send()
+            .await
+            .unwrap();
+
+        let migration = tracking.migrate_to_puuid().send().await.unwrap();
+        assert_eq!(
+            migration,
+            PuuidMigration {
+                tracking: 1,
+                snapshots: 2
+            }
+        );
+        let tracked = tracking.list().guild_id("guild").send().await.unwrap();
+        assert_eq!(tracked.len(), 1);
+        assert_eq!(tracked[0].key_id(), "puuid");
+        assert_eq!(tracking.list().send().await.unwrap().len(), 1);
+        assert_eq!(snapshots(&client, GameType::League, "puuid").await, 2);
+        assert_eq!(snapshots(&client, GameType::League, "summoner").await, 0);
+        assert_eq!(
+            tracking.migrate_to_puuid().send().await.unwrap(),
+            PuuidMigration::default()
+        );
+    }
+
+    struct TftKey;
+
+    #[async_trait]
+    impl PuuidResolver for TftKey {
+        async fn resolve(&self, item: &TrackingItem) -> Result<String> {
+            Ok(format!("tft-{}", item.puuid))
+        }
+    }
+
+    #[tokio::test]
+    async fn resolve_puuids() {
+        let client = Client::in_memory();
+        let tracking = client.tracking(GameType::Tft);
+        // Tracked with the PUUID of another API key
+        tracking
+            .track_user()
+            .guild_id("guild")
+            .puuid("puuid")
+            .account_id("account")
+            .summoner_name("monke")
+            .send()
+            .await
+            .unwrap();
+        client
+            .league_points(GameType::Tft)
+            .add()
+            .puuid("puuid")
+            .timestamp(100)
+            .tier("GOLD")
+            .rank("II")
+            .league_points(50)
+            .wins(10)
+            .losses(5)
+            .send()
+            .await
+            .unwrap();
+
+        let migration = tracking.migrate_to_puuid().send().await.unwrap();
+        assert_eq!(migration, PuuidMigration::default());
+        let migration = tracking
+            .migrate_to_puuid()
+            .resolver(TftKey)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            migration,
+            PuuidMigration {
+                tracking: 1,
+                snapshots: 1
+            }
+        );
+        let tracked = tracking.list().guild_id("guild").send().await.unwrap();
+        assert_eq!(tracked.len(), 1);
+        assert_eq!(tracked[0].puuid, "tft-puuid");
+        assert_eq!(tracked[0].key_id(), "tft-puuid");
+        assert_eq!(snapshots(&client, GameType::Tft, "tft-puuid").await, 1);
+        assert_eq!(snapshots(&client, GameType::Tft, "puuid").await, 0);
+    }
+}
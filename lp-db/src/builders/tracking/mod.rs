@@ -1,12 +1,13 @@
-use crate::{ident, GameType, Handle, ItemModel};
+use crate::{ident, GameType, Handle, ItemModel, Queue};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+pub mod migrate;
 pub mod user;
 /// Module responsible for the tracking state of the players.
 ///
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TrackingItem {
     #[serde(flatten)]
@@ -15,34 +16,91 @@ pub struct TrackingItem {
     pub puuid: String,
     pub account_id: String,
     pub summoner_name: String,
+    /// The Discord guild tracking the summoner, `None` for items stored before tracking was
+    /// scoped by guild
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guild_id: Option<String>,
+    /// The ranked queues followed for the summoner, empty for items stored before tracking
+    /// declared queues
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queues: Vec<Queue>,
     #[serde(skip)]
     pub game_type: GameType,
 }
 impl TrackingItem {
     pub fn new(
         game_type: GameType,
-        summoner_id: &str,
+        guild_id: &str,
         puuid: &str,
         account_id: &str,
         summoner_name: &str,
+        queues: &[Queue],
     ) -> Self {
-        let ident = ident(game_type);
         Self {
             item: ItemModel {
-                id: "TRACKING".to_string(),
-                sk: format!("SUMMONER#{}{}", ident, summoner_id),
+                id: tracking_partition(Some(guild_id)),
+                sk: tracking_sk(game_type, puuid, None),
             },
             puuid: puuid.to_string(),
             account_id: account_id.to_string(),
             summoner_name: summoner_name.to_string(),
+            guild_id: Some(guild_id.to_string()),
+            queues: queues.to_vec(),
             game_type,
         }
     }
-    pub fn summoner_id(&self) -> String {
-        let splits: Vec<&str> = self.item.sk.split('#').collect();
-        splits.last().unwrap_or(&"").to_string()
+    /// The ranked queues followed for the summoner, the default queue of the game when the item
+    /// does not declare any
+    pub fn tracked_queues(&self) -> Vec<Queue> {
+        match self.queues.is_empty() {
+            true => vec![Queue::default_for(self.game_type)],
+            false => self.queues.clone(),
+        }
+    }
+    /// Copy of the item stored in the shared `TRACKING` partition
+    pub fn shared(&self) -> Self {
+        let item = ItemModel {
+            id: tracking_partition(None),
+            sk: tracking_sk(self.game_type, self.key_id(), self.guild_id.as_deref()),
+        };
+        Self {
+            item,
+            ..self.clone()
+        }
+    }
+    /// The id the item is keyed by, the PUUID unless the item was stored before tracking was
+    /// keyed by PUUID, in which case it is the encrypted summoner id
+    pub(crate) fn key_id(&self) -> &str {
+        // SUMMONER#<game>#<id>, optionally followed by #GUILD#<guild id>
+        self.item.sk.split('#').nth(2).unwrap_or_default()
     }
 }
+// A summoner tracked by a guild is stored twice: once in the `TRACKING#<guild id>` partition
+// listing the summoners of the guild, and once in the shared `TRACKING` partition listing every
+// tracked summoner with the guilds tracking them.
+
+/// Partition key of the summoners tracked by a guild, or of the shared list without a guild
+pub(crate) fn tracking_partition(guild_id: Option<&str>) -> String {
+    match guild_id {
+        Some(guild_id) => format!("TRACKING#{}", guild_id),
+        None => "TRACKING".to_string(),
+    }
+}
+
+/// Sort key of a tracked player, suffixed by the guild in the shared partition
+pub(crate) fn tracking_sk(game_type: GameType, puuid: &str, guild_id: Option<&str>) -> String {
+    let ident = ident(game_type);
+    match guild_id {
+        Some(guild_id) => format!("SUMMONER#{}{}#GUILD#{}", ident, puuid, guild_id),
+        None => format!("SUMMONER#{}{}", ident, puuid),
+    }
+}
+
+/// Sort key prefix of the copies in the shared partition of a player tracked by guilds
+pub(crate) fn tracked_by_prefix(game_type: GameType, puuid: &str) -> String {
+    format!("{}#GUILD#", tracking_sk(game_type, puuid, None))
+}
+
 pub struct TrackingClient {
     handle: Arc<Handle>,
     game_type: GameType,
@@ -61,4 +119,13 @@ impl TrackingClient {
     pub fn untrack_user(&self) -> user::UntrackUserBuilder {
         user::UntrackUserBuilder::new(self.handle.clone(), self.game_type)
     }
+    pub fn tracked_by(&self) -> user::TrackedByBuilder {
+        user::TrackedByBuilder::new(self.handle.clone(), self.game_type)
+    }
+    pub fn migrate_to_guild(&self) -> migrate::MigrateToGuildBuilder {
+        migrate::MigrateToGuildBuilder::new(self.handle.clone(), self.game_type)
+    }
+    pub fn migrate_to_puuid(&self) -> migrate::MigrateToPuuidBuilder {
+        migrate::MigrateToPuuidBuilder::new(self.handle.clone(), self.game_type)
+    }
 }
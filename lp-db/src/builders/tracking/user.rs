@@ -1,19 +1,26 @@
-use super::{ident, TrackingItem};
-use crate::error::Result;
-use crate::{GameType, Handle};
-use aws_sdk_dynamodb::types::AttributeValue;
-use serde_dynamo::aws_sdk_dynamodb_0_25::{from_items, to_item};
+use super::TrackingItem;
+use crate::builders::audit::{entry, AuditAction};
+use crate::error::{Error, Result};
+use crate::store::paginate;
+use crate::{check_queue, GameType, Handle, Queue};
+use futures::future;
+use futures::stream::{Stream, TryStreamExt};
+use std::collections::{HashMap, HashSet};
 
 pub struct TrackUserBuilder {
     handle: std::sync::Arc<Handle>,
-    /// The encrypted summoner id
-    id: Option<String>,
+    /// The Discord guild tracking the summoner
+    guild_id: Option<String>,
     /// The PUUID of the summoner
     puuid: Option<String>,
     /// The account id of the summoner
     account_id: Option<String>,
     /// The summoner name
     summoner_name: Option<String>,
+    /// The ranked queues to follow
+    queues: Vec<Queue>,
+    /// The Discord user tracking the summoner, for the audit log
+    actor: Option<String>,
     game_type: GameType,
 }
 
@@ -21,15 +28,17 @@ impl TrackUserBuilder {
     pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
         Self {
             handle,
-            id: None,
+            guild_id: None,
             puuid: None,
             account_id: None,
             summoner_name: None,
+            queues: Vec::new(),
+            actor: None,
             game_type,
         }
     }
-    pub fn id(mut self, id: &str) -> Self {
-        self.id = Some(id.to_string());
+    pub fn guild_id(mut self, guild_id: &str) -> Self {
+        self.guild_id = Some(guild_id.to_string());
         self
     }
     pub fn puuid(mut self, puuid: &str) -> Self {
@@ -45,58 +54,142 @@ impl TrackUserBuilder {
         self.summoner_name = Some(summoner_name.to_string());
         self
     }
+    /// Defaults to the default queue of the game (Solo/Duo or TFT Ranked)
+    pub fn queues(mut self, queues: &[Queue]) -> Self {
+        self.queues = queues.to_vec();
+        self
+    }
+    pub fn actor(mut self, discord_user_id: &str) -> Self {
+        self.actor = Some(discord_user_id.to_string());
+        self
+    }
+    /// Fails with [`Error::AlreadyTracked`] when the guild already tracks the summoner, even
+    /// when tracked concurrently
     pub async fn send(self) -> Result<()> {
+        for queue in &self.queues {
+            check_queue(self.game_type, *queue)?;
+        }
+        let guild_id = self.guild_id.ok_or_else(|| Error::missing("guild_id"))?;
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
         let item = TrackingItem::new(
             self.game_type,
-            &self.id.expect("id is required"),
-            &self.puuid.expect("puuid is required"),
-            &self.account_id.expect("account_id is required"),
-            &self.summoner_name.expect("summoner_name is required"),
+            &guild_id,
+            &puuid,
+            &self
+                .account_id
+                .ok_or_else(|| Error::missing("account_id"))?,
+            &self
+                .summoner_name
+                .ok_or_else(|| Error::missing("summoner_name"))?,
+            &self.queues,
+        );
+        let queues = self
+            .queues
+            .iter()
+            .map(|queue| queue.riot_queue_type())
+            .collect::<Vec<_>>();
+        let audit = entry(
+            &guild_id,
+            self.actor.as_deref(),
+            AuditAction::Track,
+            &puuid,
+            Some(self.game_type),
+            (!queues.is_empty()).then(|| queues.join(",")),
         );
         self.handle
-            .inner
-            .put_item()
-            .table_name(self.handle.table_name.as_str())
-            .set_item(Some(to_item(&item).unwrap()))
-            .send()
-            .await?;
-        Ok(())
+            .store
+            .track_new(item, Some(serde_json::to_value(&audit)?))
+            .await
     }
 }
 
 pub struct ListUserBuilder {
     handle: std::sync::Arc<Handle>,
+    /// Only list the summoners tracked by this guild
+    guild_id: Option<String>,
     game_type: GameType,
 }
 
 impl ListUserBuilder {
     pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
-        Self { handle, game_type }
+        Self {
+            handle,
+            guild_id: None,
+            game_type,
+        }
     }
+    /// Without a guild, every tracked summoner is listed once whatever the guilds tracking them
+    pub fn guild_id(mut self, guild_id: &str) -> Self {
+        self.guild_id = Some(guild_id.to_string());
+        self
+    }
+    /// Stream the tracked summoners, fetching the next page only once the current one is consumed
+    ///
+    /// Without a guild, the queues of a summoner are those followed by one of the guilds tracking
+    /// it, [`Self::send`] merges them.
+    pub fn into_stream(self) -> impl Stream<Item = Result<TrackingItem>> {
+        let shared = self.guild_id.is_none();
+        let mut seen = HashSet::new();
+        self.rows()
+            .try_filter(move |item| future::ready(!shared || seen.insert(item.puuid.clone())))
+    }
+    /// Without a guild, the queues of each summoner are every queue followed by the guilds
+    /// tracking it
     pub async fn send(self) -> Result<Vec<TrackingItem>> {
-        let ident = ident(self.game_type);
-        let res = self
-            .handle
-            .inner
-            .query()
-            .table_name(self.handle.table_name.as_str())
-            .key_condition_expression("id = :id AND begins_with(sk, :sk)")
-            .expression_attribute_values(":id", AttributeValue::S("TRACKING".to_string()))
-            .expression_attribute_values(":sk", AttributeValue::S(format!("SUMMONER#{}", ident)))
-            .send()
-            .await?;
-
-        match res.items {
-            Some(items) => Ok(from_items(items).unwrap()),
-            None => Ok(vec![]),
+        if self.guild_id.is_some() {
+            return self.rows().try_collect().await;
+        }
+        let rows: Vec<TrackingItem> = self.rows().try_collect().await?;
+        let mut summoners: Vec<TrackingItem> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for row in rows {
+            let queues = row.tracked_queues();
+            match index.get(&row.puuid) {
+                Some(&i) => {
+                    let summoner = &mut summoners[i];
+                    for queue in queues {
+                        if !summoner.queues.contains(&queue) {
+                            summoner.queues.push(queue);
+                        }
+                    }
+                }
+                None => {
+                    index.insert(row.puuid.clone(), summoners.len());
+                    summoners.push(TrackingItem { queues, ..row });
+                }
+            }
         }
+        Ok(summoners)
+    }
+    /// Every tracking item, a summoner tracked by many guilds has one per guild in the shared list
+    fn rows(self) -> impl Stream<Item = Result<TrackingItem>> {
+        let (handle, game_type, guild_id) = (self.handle, self.game_type, self.guild_id);
+        paginate(move |start| {
+            let handle = handle.clone();
+            let guild_id = guild_id.clone();
+            async move {
+                handle
+                    .store
+                    .list_tracked(game_type, guild_id.as_deref(), start)
+                    .await
+            }
+        })
+        // The game type is not stored with the item
+        .map_ok(move |mut item| {
+            item.game_type = game_type;
+            item
+        })
     }
 }
 
 pub struct UntrackUserBuilder {
     handle: std::sync::Arc<Handle>,
-    /// The encrypted summoner id
-    id: Option<String>,
+    /// The Discord guild tracking the summoner
+    guild_id: Option<String>,
+    /// The PUUID of the summoner
+    puuid: Option<String>,
+    /// The Discord user untracking the summoner, for the audit log
+    actor: Option<String>,
     game_type: GameType,
 }
 
@@ -104,25 +197,171 @@ impl UntrackUserBuilder {
     pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
         Self {
             handle,
-            id: None,
+            guild_id: None,
+            puuid: None,
+            actor: None,
             game_type,
         }
     }
-    pub fn id(mut self, id: &str) -> Self {
-        self.id = Some(id.to_string());
+    pub fn guild_id(mut self, guild_id: &str) -> Self {
+        self.guild_id = Some(guild_id.to_string());
         self
     }
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub fn actor(mut self, discord_user_id: &str) -> Self {
+        self.actor = Some(discord_user_id.to_string());
+        self
+    }
+    /// Fails with [`Error::NotTracked`] when the guild does not track the summoner
     pub async fn send(self) -> Result<()> {
-        let id = &self.id.expect("id is required");
-        let ident = ident(self.game_type);
+        let guild_id = self.guild_id.ok_or_else(|| Error::missing("guild_id"))?;
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        let audit = entry(
+            &guild_id,
+            self.actor.as_deref(),
+            AuditAction::Untrack,
+            &puuid,
+            Some(self.game_type),
+            None,
+        );
         self.handle
-            .inner
-            .delete_item()
-            .table_name(self.handle.table_name.as_str())
-            .key("id", AttributeValue::S("TRACKING".to_string()))
-            .key("sk", AttributeValue::S(format!("SUMMONER#{}{}", ident, id)))
+            .store
+            .untrack(
+                self.game_type,
+                Some(&guild_id),
+                &puuid,
+                Some(serde_json::to_value(&audit)?),
+            )
+            .await
+    }
+}
+
+/// Find the guilds tracking a summoner
+pub struct TrackedByBuilder {
+    handle: std::sync::Arc<Handle>,
+    /// The PUUID of the summoner
+    puuid: Option<String>,
+    game_type: GameType,
+}
+
+impl TrackedByBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>, game_type: GameType) -> Self {
+        Self {
+            handle,
+            puuid: None,
+            game_type,
+        }
+    }
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    /// Get the ids of the guilds tracking the summoner
+    pub async fn send(self) -> Result<Vec<String>> {
+        let puuid = self.puuid.ok_or_else(|| Error::missing("puuid"))?;
+        let (handle, game_type) = (self.handle, self.game_type);
+        paginate(move |start| {
+            let handle = handle.clone();
+            let puuid = puuid.clone();
+            async move { handle.store.list_tracked_by(game_type, &puuid, start).await }
+        })
+        .try_filter_map(|item: TrackingItem| future::ready(Ok(item.guild_id)))
+        .try_collect()
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::Client;
+
+    #[tokio::test]
+    async fn guild_tracking() {
+        let client = Client::in_memory();
+        let tracking = client.tracking(GameType::League);
+        for (guild_id, queues) in [("a", vec![]), ("b", vec![Queue::Flex])] {
+            tracking
+                .track_user()
+                .guild_id(guild_id)
+                .puuid("puuid")
+                .account_id("account")
+                .summoner_name("monke")
+                .queues(&queues)
+                .send()
+                .await
+                .unwrap();
+        }
+        // Its PUUID starts with the other one, but it is tracked by another guild
+        tracking
+            .track_user()
+            .guild_id("c")
+            .puuid("puuid2")
+            .account_id("account2")
+            .summoner_name("monke2")
+            .send()
+            .await
+            .unwrap();
+        let tracked = tracking.list().send().await.unwrap();
+        assert_eq!(tracked.len(), 2);
+        // The queues followed by every guild tracking the summoner
+        assert_eq!(tracked[0].tracked_queues(), vec![Queue::Solo, Queue::Flex]);
+        assert_eq!(tracking.list().guild_id("a").send().await.unwrap().len(), 1);
+        let guilds = tracking.tracked_by().puuid("puuid").send().await.unwrap();
+        assert_eq!(guilds, vec!["a", "b"]);
+
+        tracking
+            .untrack_user()
+            .guild_id("a")
+            .puuid("puuid")
+            .send()
+            .await
+            .unwrap();
+        assert!(tracking
+            .list()
+            .guild_id("a")
             .send()
-            .await?;
-        Ok(())
+            .await
+            .unwrap()
+            .is_empty());
+        let guilds = tracking.tracked_by().puuid("puuid").send().await.unwrap();
+        assert_eq!(guilds, vec!["b"]);
+    }
+
+    #[tokio::test]
+    async fn tracking_conflicts() {
+        let client = Client::in_memory();
+        let tracking = client.tracking(GameType::League);
+        let track = || {
+            tracking
+                .track_user()
+                .guild_id("guild")
+                .puuid("puuid")
+                .account_id("account")
+                .summoner_name("monke")
+                .send()
+        };
+        let (first, second) = futures::join!(track(), track());
+        assert!(matches!(
+            (first, second),
+            (Ok(()), Err(Error::AlreadyTracked)) | (Err(Error::AlreadyTracked), Ok(()))
+        ));
+        assert_eq!(tracking.list().send().await.unwrap().len(), 1);
+
+        let untrack = |guild_id: &str| {
+            tracking
+                .untrack_user()
+                .guild_id(guild_id)
+                .puuid("puuid")
+                .send()
+        };
+        assert!(matches!(untrack("other").await, Err(Error::NotTracked)));
+        untrack("guild").await.unwrap();
+        assert!(matches!(untrack("guild").await, Err(Error::NotTracked)));
+        assert!(tracking.list().send().await.unwrap().is_empty());
     }
 }
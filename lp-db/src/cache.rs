@@ -0,0 +1,373 @@
+//! Cache of the match details fetched from the Riot API, shared by the bot and the LP service
+use crate::error::{Error, Result};
+use crate::store::LpStore;
+use crate::{ident, Client, GameType, Handle, ItemModel};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Version of the compressed match payloads written by this crate
+pub const SCHEMA_VERSION: u32 = 1;
+/// zstd level of the payloads, favoring speed as the cache is written on user requests
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+/// Match details stored under the match id, the sort key holds the game type
+///
+/// Rows without a schema version hold the details as attributes, as written without a
+/// compression level and by the bot's former cache, see [`MatchCache::legacy`].
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedMatch<T> {
+    #[serde(flatten)]
+    item: ItemModel,
+    #[serde(flatten)]
+    details: T,
+}
+
+/// Match details stored as a zstd-compressed JSON payload
+///
+/// The payload is base64 encoded so that every store keeps JSON items.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompressedMatch {
+    #[serde(flatten)]
+    item: ItemModel,
+    schema_version: u32,
+    payload: String,
+}
+
+impl CompressedMatch {
+    fn encode<T: Serialize>(item: ItemModel, details: &T, level: i32) -> Result<Self> {
+        let json = serde_json::to_vec(details)?;
+        let compressed =
+            zstd::encode_all(json.as_slice(), level).map_err(|e| Error::Storage(e.to_string()))?;
+        Ok(Self {
+            item,
+            schema_version: SCHEMA_VERSION,
+            payload: BASE64.encode(compressed),
+        })
+    }
+    fn decode<T: DeserializeOwned>(self) -> Result<(String, T)> {
+        if self.schema_version != SCHEMA_VERSION {
+            return Err(Error::Validation(format!(
+                "Unsupported schema version {} of match {}",
+                self.schema_version, self.item.id
+            )));
+        }
+        let invalid = |e: &dyn std::fmt::Display| {
+            Error::Validation(format!("Invalid payload of match {}: {}", self.item.id, e))
+        };
+        let compressed = BASE64.decode(&self.payload).map_err(|e| invalid(&e))?;
+        let json = zstd::decode_all(compressed.as_slice()).map_err(|e| invalid(&e))?;
+        let details = serde_json::from_slice(&json)?;
+        Ok((self.item.id, details))
+    }
+}
+
+/// Decode a cached row of either format
+fn decode<T: DeserializeOwned>(item: Value) -> Result<(String, T)> {
+    if item.get("schemaVersion").is_some() {
+        return serde_json::from_value::<CompressedMatch>(item)?.decode();
+    }
+    let cached: CachedMatch<T> = serde_json::from_value(item)?;
+    Ok((cached.item.id, cached.details))
+}
+
+/// Matches of a [`MatchCache::get_or_load`] call
+#[derive(Debug)]
+pub struct CachedMatches<T> {
+    /// `(match id, details)` pairs in the requested order, without duplicates
+    pub matches: Vec<(String, T)>,
+    /// Matches neither in the cache nor returned by the loader
+    pub missing: Vec<String>,
+}
+
+impl<T> Default for CachedMatches<T> {
+    fn default() -> Self {
+        Self {
+            matches: Vec::new(),
+            missing: Vec::new(),
+        }
+    }
+}
+
+pub struct MatchCache {
+    handle: Arc<Handle>,
+    game_type: GameType,
+    compression_level: Option<i32>,
+    /// Store of the cache written before the match cache moved to this crate
+    legacy: Option<Arc<dyn LpStore>>,
+}
+
+impl MatchCache {
+    pub fn new(handle: Arc<Handle>, game_type: GameType) -> Self {
+        Self {
+            handle,
+            game_type,
+            compression_level: Some(DEFAULT_COMPRESSION_LEVEL),
+            legacy: None,
+        }
+    }
+    /// zstd level of the payloads written, `None` to store the details as attributes
+    ///
+    /// Rows of both formats are read regardless of this level.
+    pub fn compression_level(mut self, level: Option<i32>) -> Self {
+        self.compression_level = level;
+        self
+    }
+    /// Read the matches missing from the cache from the bot's former cache in the table of
+    /// `legacy`, copying the ones found to this cache
+    ///
+    /// The former cache keyed the matches by match id and `#` (League) or `#TFT` (TFT), with the
+    /// details stored as attributes.
+    pub fn legacy(mut self, legacy: &Client) -> Self {
+        self.legacy = Some(legacy.handle.store.clone());
+        self
+    }
+    fn key(&self, match_id: &str) -> ItemModel {
+        ItemModel {
+            id: match_id.to_string(),
+            sk: format!("MATCH#{}", ident(self.game_type)),
+        }
+    }
+    fn legacy_key(&self, match_id: &str) -> ItemModel {
+        let sk = match self.game_type {
+            GameType::League => "#",
+            GameType::Tft => "#TFT",
+        };
+        ItemModel {
+            id: match_id.to_string(),
+            sk: sk.to_string(),
+        }
+    }
+    /// Get the cached details of these matches, in any order, uncached matches are skipped
+    pub async fn get<T: Serialize + DeserializeOwned>(
+        &self,
+        match_ids: &[String],
+    ) -> Result<Vec<(String, T)>> {
+        // BatchGetItem rejects duplicated keys
+        let match_ids = match_ids.iter().collect::<BTreeSet<_>>();
+        if match_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let keys = match_ids.iter().map(|id| self.key(id)).collect::<Vec<_>>();
+        let items = self.handle.store.get_items(&keys).await?;
+        let mut matches = items
+            .into_iter()
+            .map(decode)
+            .collect::<Result<Vec<(String, T)>>>()?;
+        let Some(legacy) = &self.legacy else {
+            return Ok(matches);
+        };
+        let cached = matches.iter().map(|(id, _)| id).collect::<HashSet<_>>();
+        let keys = match_ids
+            .iter()
+            .filter(|id| !cached.contains(**id))
+            .map(|id| self.legacy_key(id))
+            .collect::<Vec<_>>();
+        if keys.is_empty() {
+            return Ok(matches);
+        }
+        let copied = legacy
+            .get_items(&keys)
+            .await?
+            .into_iter()
+            .map(decode)
+            .collect::<Result<Vec<(String, T)>>>()?;
+        self.put(&copied).await?;
+        matches.extend(copied);
+        Ok(matches)
+    }
+    /// Store the details of these matches, replacing the cached ones
+    pub async fn put<T: Serialize>(&self, matches: &[(String, T)]) -> Result<()> {
+        if matches.is_empty() {
+            return Ok(());
+        }
+        let items = matches
+            .iter()
+            .map(|(id, details)| {
+                let item = self.key(id);
+                Ok(match self.compression_level {
+                    Some(level) => {
+                        serde_json::to_value(CompressedMatch::encode(item, details, level)?)?
+                    }
+                    None => serde_json::to_value(CachedMatch { item, details })?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.handle.store.put_items(items).await
+    }
+    /// Get the details of these matches, loading the uncached ones with `loader`
+    ///
+    /// `loader` is called once with the uncached match ids, and only when some are uncached. The
+    /// matches it returns are cached for the next calls, the others are listed in `missing`.
+    pub async fn get_or_load<T, F, Fut>(
+        &self,
+        match_ids: &[String],
+        loader: F,
+    ) -> Result<CachedMatches<T>>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce(Vec<String>) -> Fut,
+        Fut: Future<Output = Vec<(String, T)>>,
+    {
+        let mut found = self
+            .get(match_ids)
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let mut seen = HashSet::new();
+        let uncached = match_ids
+            .iter()
+            .filter(|id| !found.contains_key(*id) && seen.insert(*id))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !uncached.is_empty() {
+            let loaded = loader(uncached).await;
+            self.put(&loaded).await?;
+            found.extend(loaded);
+        }
+
+        let mut matches = CachedMatches::default();
+        seen.clear();
+        for id in match_ids.iter().filter(|id| seen.insert(*id)) {
+            match found.remove(id) {
+                Some(details) => matches.matches.push((id.clone(), details)),
+                None => matches.missing.push(id.clone()),
+            }
+        }
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Details {
+        duration: i64,
+    }
+
+    #[tokio::test]
+    async fn legacy_match_cache() {
+        let client = Client::in_memory();
+        let legacy = Client::in_memory();
+        legacy
+            .handle
+            .store
+            .put_items(vec![
+                json!({"id": "NA1_1", "sk": "#", "duration": 1800}),
+                json!({"id": "NA1_2", "sk": "#TFT", "duration": 1200}),
+            ])
+            .await
+            .unwrap();
+        let cache = client.match_cache(GameType::League).legacy(&legacy);
+        let ids = ["NA1_1", "NA1_1", "NA1_2"].map(str::to_string);
+
+        let cached = cache.get::<Details>(&ids).await.unwrap();
+        assert_eq!(
+            cached,
+            vec![("NA1_1".to_string(), Details { duration: 1800 })]
+        );
+        // Copied to the cache, in the compressed format
+        let copied = client
+            .handle
+            .store
+            .get_items(&[cache.key("NA1_1")])
+            .await
+            .unwrap();
+        assert_eq!(copied[0]["schemaVersion"], SCHEMA_VERSION);
+
+        let tft = client.match_cache(GameType::Tft).legacy(&legacy);
+        let cached = tft.get::<Details>(&ids).await.unwrap();
+        assert_eq!(
+            cached,
+            vec![("NA1_2".to_string(), Details { duration: 1200 })]
+        );
+    }
+
+    #[tokio::test]
+    async fn match_cache() {
+        let client = Client::in_memory();
+        let cache = client.match_cache(GameType::League);
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        let loaded = cache
+            .get_or_load(&ids(&["NA1_1", "NA1_2", "NA1_3"]), |uncached| async move {
+                assert_eq!(uncached.len(), 3);
+                uncached
+                    .into_iter()
+                    .filter(|id| id != "NA1_3")
+                    .map(|id| (id, Details { duration: 1800 }))
+                    .collect()
+            })
+            .await
+            .unwrap();
+        assert_eq!(loaded.matches.len(), 2);
+        assert_eq!(loaded.missing, ids(&["NA1_3"]));
+
+        let loaded = cache
+            .get_or_load(&ids(&["NA1_4", "NA1_2", "NA1_1"]), |uncached| async move {
+                assert_eq!(uncached, ids(&["NA1_4"]));
+                vec![("NA1_4".to_string(), Details { duration: 1200 })]
+            })
+            .await
+            .unwrap();
+        let order: Vec<_> = loaded.matches.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(order, vec!["NA1_4", "NA1_2", "NA1_1"]);
+        assert!(loaded.missing.is_empty());
+
+        let cached = client
+            .match_cache(GameType::Tft)
+            .get::<Details>(&ids(&["NA1_1"]))
+            .await
+            .unwrap();
+        assert!(cached.is_empty());
+    }
+
+    #[tokio::test]
+    async fn compressed_match_cache() {
+        let client = Client::in_memory();
+        let cache = client.match_cache(GameType::Tft);
+        cache
+            .put(&[("NA1_1".to_string(), Details { duration: 1800 })])
+            .await
+            .unwrap();
+        client
+            .match_cache(GameType::Tft)
+            .compression_level(None)
+            .put(&[("NA1_2".to_string(), Details { duration: 1200 })])
+            .await
+            .unwrap();
+
+        let keys = ["NA1_1", "NA1_2"].map(|id| ItemModel {
+            id: id.to_string(),
+            sk: "MATCH#TFT#".to_string(),
+        });
+        let mut rows = client.handle.store.get_items(&keys).await.unwrap();
+        rows.sort_by_key(|row| row["id"].as_str().map(str::to_string));
+        assert_eq!(rows[0]["schemaVersion"], 1);
+        assert!(rows[0].get("duration").is_none());
+        assert!(rows[1].get("schemaVersion").is_none());
+        assert_eq!(rows[1]["duration"], 1200);
+
+        let mut cached = cache
+            .get::<Details>(&["NA1_1".to_string(), "NA1_2".to_string()])
+            .await
+            .unwrap();
+        cached.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            cached,
+            vec![
+                ("NA1_1".to_string(), Details { duration: 1800 }),
+                ("NA1_2".to_string(), Details { duration: 1200 }),
+            ]
+        );
+    }
+}
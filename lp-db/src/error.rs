@@ -8,8 +8,20 @@ pub enum Error {
     SerializeError(serde_json::Error),
     Validation(String),
     AwsSdk(String),
+    /// Error of a local storage backend
+    Storage(String),
+    /// The guild already tracks the summoner
+    AlreadyTracked,
+    /// The guild does not track the summoner
+    NotTracked,
 }
 
+impl Error {
+    /// A builder was sent without one of its required fields
+    pub(crate) fn missing(field: &str) -> Self {
+        Self::Validation(format!("{} is required", field))
+    }
+}
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -26,16 +38,30 @@ impl<T: std::error::Error + 'static + Send + Sync + CreateUnhandledError>
         Self::AwsSdk(msg)
     }
 }
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::SerializeError(err)
+    }
+}
 impl From<serde_dynamo::Error> for Error {
     fn from(err: serde_dynamo::Error) -> Self {
         Self::Validation(err.to_string())
     }
 }
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::Storage(err.to_string())
+    }
+}
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let msg = match self {
             Error::SerializeError(e) => return e.fmt(f),
             Error::Validation(e) => e,
+            Error::Storage(e) => e,
+            Error::AlreadyTracked => "Summoner is already tracked",
+            Error::NotTracked => "Summoner is not tracked",
             Error::AwsSdk(e) => {
                 writeln!(f, "AwsSdk error: {}", e)?;
                 "Aws sdk error"
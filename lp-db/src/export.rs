@@ -0,0 +1,379 @@
+//! Export and import of the tracking rows, league points history and player profiles
+//!
+//! Exports are written as newline-delimited JSON, one [`Record`] per line, so they can be
+//! restored into any store: another DynamoDB table, a local SQLite database, or handed to a
+//! player as their own history. The LP history alone can also be written as CSV.
+use crate::builders::league_points::between::GetBetweenBuilder;
+use crate::builders::league_points::{day_key, LpItem};
+use crate::builders::profile::get::get_profile;
+use crate::builders::profile::PlayerProfile;
+use crate::builders::tracking::TrackingItem;
+use crate::error::{Error, Result};
+use crate::store::paginate;
+use crate::{GameType, Handle, Queue};
+use futures::stream::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+/// One exported item
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Record {
+    /// A summoner tracked by a guild, or stored before tracking was scoped by guild
+    Tracking {
+        #[serde(rename = "gameType")]
+        game_type: GameType,
+        #[serde(flatten)]
+        item: TrackingItem,
+    },
+    Snapshot(LpItem),
+    Profile(PlayerProfile),
+}
+
+/// A row of the CSV export of the LP history
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvSnapshot {
+    puuid: String,
+    queue: Queue,
+    timestamp: i64,
+    tier: String,
+    rank: String,
+    league_points: i32,
+    wins: i64,
+    losses: i64,
+    split: Option<String>,
+}
+
+/// The records of an export, see [`crate::Client::export`]
+#[derive(Debug, Default)]
+pub struct Export {
+    pub records: Vec<Record>,
+}
+
+impl Export {
+    pub fn write_ndjson(&self, mut out: impl Write) -> Result<()> {
+        for record in &self.records {
+            serde_json::to_writer(&mut out, record)?;
+            out.write_all(b"\n").map_err(io_error)?;
+        }
+        out.flush().map_err(io_error)
+    }
+    pub fn read_ndjson(input: impl BufRead) -> Result<Self> {
+        let mut records = Vec::new();
+        for line in input.lines() {
+            let line = line.map_err(io_error)?;
+            if !line.trim().is_empty() {
+                records.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(Self { records })
+    }
+    /// Write the snapshots as CSV, the tracking rows and profiles are left out
+    pub fn write_csv(&self, out: impl Write) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(out);
+        for record in &self.records {
+            let Record::Snapshot(snapshot) = record else {
+                continue;
+            };
+            let queue = snapshot.queue().ok_or_else(|| {
+                Error::Validation(format!("{} is not a snapshot", snapshot.item.sk))
+            })?;
+            writer
+                .serialize(CsvSnapshot {
+                    puuid: snapshot.puuid().to_string(),
+                    queue,
+                    timestamp: snapshot.timestamp().timestamp(),
+                    tier: snapshot.tier.clone(),
+                    rank: snapshot.rank.clone(),
+                    league_points: snapshot.league_points,
+                    wins: snapshot.wins,
+                    losses: snapshot.losses,
+                    split: snapshot.split.clone(),
+                })
+                .map_err(csv_error)?;
+        }
+        writer.flush().map_err(io_error)
+    }
+    /// Read snapshots written by [`Self::write_csv`]
+    pub fn read_csv(input: impl std::io::Read) -> Result<Self> {
+        let mut reader = csv::Reader::from_reader(input);
+        let mut records = Vec::new();
+        for row in reader.deserialize() {
+            let row: CsvSnapshot = row.map_err(csv_error)?;
+            let mut snapshot = LpItem::new(
+                row.queue,
+                &row.puuid,
+                row.timestamp,
+                &row.tier,
+                &row.rank,
+                row.league_points,
+                row.wins,
+                row.losses,
+            );
+            snapshot.split = row.split;
+            records.push(Record::Snapshot(snapshot));
+        }
+        Ok(Self { records })
+    }
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::Storage(e.to_string())
+}
+
+fn csv_error(e: csv::Error) -> Error {
+    match e.kind() {
+        csv::ErrorKind::Io(_) => Error::Storage(e.to_string()),
+        _ => Error::Validation(e.to_string()),
+    }
+}
+
+/// Collect the records to export
+///
+/// Players are found through the tracking rows, unless exporting a single player.
+pub struct ExportBuilder {
+    handle: Arc<Handle>,
+    /// Only export the snapshots and profile of this player
+    puuid: Option<String>,
+    /// Only export the items of this game
+    game_type: Option<GameType>,
+}
+
+impl ExportBuilder {
+    pub fn new(handle: Arc<Handle>) -> Self {
+        Self {
+            handle,
+            puuid: None,
+            game_type: None,
+        }
+    }
+    pub fn puuid(mut self, puuid: &str) -> Self {
+        self.puuid = Some(puuid.to_string());
+        self
+    }
+    pub fn game_type(mut self, game_type: GameType) -> Self {
+        self.game_type = Some(game_type);
+        self
+    }
+    pub async fn send(self) -> Result<Export> {
+        let game_types = match self.game_type {
+            Some(game_type) => vec![game_type],
+            None => vec![GameType::League, GameType::Tft],
+        };
+        let mut export = Export::default();
+        let mut puuids = BTreeSet::new();
+        match &self.puuid {
+            Some(puuid) => {
+                puuids.insert(puuid.clone());
+            }
+            None => {
+                for game_type in &game_types {
+                    for item in self.tracking_rows(*game_type).await? {
+                        puuids.insert(item.puuid.clone());
+                        export.records.push(Record::Tracking {
+                            game_type: *game_type,
+                            item,
+                        });
+                    }
+                }
+            }
+        }
+        for puuid in &puuids {
+            if let Some(profile) = get_profile(&self.handle, puuid).await? {
+                export.records.push(Record::Profile(profile));
+            }
+            for queue in Queue::ALL
+                .into_iter()
+                .filter(|queue| game_types.contains(&queue.game_type()))
+            {
+                let snapshots = GetBetweenBuilder::new(self.handle.clone(), queue.game_type())
+                    .puuid(puuid)
+                    .queue(queue)
+                    .start_time(0)
+                    .end_time(i64::MAX)
+                    .send()
+                    .await?
+                    .unwrap_or_default();
+                export
+                    .records
+                    .extend(snapshots.into_iter().map(Record::Snapshot));
+            }
+        }
+        Ok(export)
+    }
+    /// The tracking rows of every guild, rebuilt from their copies in the shared partition
+    async fn tracking_rows(&self, game_type: GameType) -> Result<Vec<TrackingItem>> {
+        let handle = self.handle.clone();
+        let shared: Vec<TrackingItem> = paginate(move |start| {
+            let handle = handle.clone();
+            async move { handle.store.list_tracked(game_type, None, start).await }
+        })
+        .try_collect()
+        .await?;
+        Ok(shared
+            .into_iter()
+            .map(|item| match &item.guild_id {
+                Some(guild_id) => TrackingItem::new(
+                    game_type,
+                    guild_id,
+                    &item.puuid,
+                    &item.account_id,
+                    &item.summoner_name,
+                    &item.queues,
+                ),
+                // Stored before tracking was scoped by guild, only in the shared partition
+                None => TrackingItem { game_type, ..item },
+            })
+            .collect())
+    }
+}
+
+/// Restore exported records, replacing the existing items with the same keys
+pub struct ImportBuilder {
+    handle: Arc<Handle>,
+    records: Vec<Record>,
+}
+
+impl ImportBuilder {
+    pub fn new(handle: Arc<Handle>, export: Export) -> Self {
+        Self {
+            handle,
+            records: export.records,
+        }
+    }
+    /// Returns the number of imported records
+    pub async fn send(self) -> Result<usize> {
+        let imported = self.records.len();
+        let mut tracking = Vec::new();
+        let mut snapshots = Vec::new();
+        let mut profiles = Vec::new();
+        for record in self.records {
+            match record {
+                Record::Tracking { game_type, item } => {
+                    tracking.push(TrackingItem { game_type, ..item })
+                }
+                Record::Snapshot(mut snapshot) => {
+                    let Some(queue) = snapshot.queue() else {
+                        return Err(Error::Validation(format!(
+                            "{} is not a snapshot",
+                            snapshot.item.sk
+                        )));
+                    };
+                    // Exports written before the byDay index have no day
+                    if snapshot.day.is_none() {
+                        snapshot.day = day_key(queue.game_type(), snapshot.timestamp().timestamp());
+                    }
+                    snapshots.push(snapshot);
+                }
+                Record::Profile(profile) => profiles.push(serde_json::to_value(profile)?),
+            }
+        }
+        for item in tracking {
+            self.handle.store.track(item).await?;
+        }
+        if !snapshots.is_empty() {
+            self.handle.store.add_many(snapshots).await?;
+        }
+        if !profiles.is_empty() {
+            self.handle.store.put_items(profiles).await?;
+        }
+        Ok(imported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+
+    #[tokio::test]
+    async fn export_and_import() {
+        const T: i64 = 1704085200;
+        let client = Client::in_memory();
+        for (guild_id, game_type) in [("a", GameType::League), ("b", GameType::Tft)] {
+            client
+                .tracking(game_type)
+                .track_user()
+                .guild_id(guild_id)
+                .puuid("puuid")
+                .account_id("account")
+                .summoner_name("monke")
+                .send()
+                .await
+                .unwrap();
+        }
+        let snapshots = vec![
+            LpItem::new(Queue::Solo, "puuid", T, "GOLD", "I", 10, 5, 5),
+            LpItem::new(Queue::Flex, "puuid", T, "SILVER", "II", 20, 1, 2),
+        ];
+        let league_points = client.league_points(GameType::League);
+        league_points.add_many(snapshots).send().await.unwrap();
+        client
+            .profiles()
+            .create()
+            .puuid("puuid")
+            .riot_id("monke#NA1")
+            .send()
+            .await
+            .unwrap();
+
+        let mut ndjson = Vec::new();
+        let export = client.export().send().await.unwrap();
+        assert_eq!(export.records.len(), 5);
+        export.write_ndjson(&mut ndjson).unwrap();
+
+        let restored = Client::in_memory();
+        let export = Export::read_ndjson(ndjson.as_slice()).unwrap();
+        assert_eq!(restored.import(export).send().await.unwrap(), 5);
+        let guilds = restored
+            .tracking(GameType::Tft)
+            .tracked_by()
+            .puuid("puuid")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(guilds, vec!["b"]);
+        let history = restored
+            .league_points(GameType::League)
+            .history("puuid", ..)
+            .queue(Queue::Flex)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(history[0].tier, "SILVER");
+        let profile = restored
+            .profiles()
+            .get()
+            .puuid("puuid")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(profile.unwrap().riot_id, "monke#NA1");
+
+        // A player's own LP history as CSV
+        let mut csv = Vec::new();
+        let export = client
+            .export()
+            .puuid("puuid")
+            .game_type(GameType::League)
+            .send()
+            .await
+            .unwrap();
+        export.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.starts_with("puuid,queue,timestamp,tier,rank,league_points"));
+        let export = Export::read_csv(csv.as_bytes()).unwrap();
+        let restored = Client::in_memory();
+        assert_eq!(restored.import(export).send().await.unwrap(), 2);
+        let latest = restored
+            .league_points(GameType::League)
+            .latest("puuid")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(latest.unwrap().league_points, 10);
+    }
+}
@@ -1,16 +1,24 @@
 pub mod error;
 
 use aws_config::SdkConfig;
-use aws_sdk_dynamodb::Client as DynamoDbClient;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use store::{DynamoStore, LpStore, MemoryStore};
 
 pub mod builders;
+pub mod cache;
+pub mod export;
+pub mod migration;
+pub mod season;
+pub mod store;
 
 #[derive(Debug)]
 pub struct Handle {
-    pub inner: DynamoDbClient,
-    pub table_name: String,
+    pub store: Arc<dyn LpStore>,
+    /// The ranked splits snapshots are tagged with, empty unless configured
+    pub seasons: season::SeasonCalendar,
+    /// Sets the expiry of the snapshots written, and allows removing the old ones
+    pub retention: Option<builders::league_points::retention::RetentionPolicy>,
 }
 /// Database client for the LP service
 ///
@@ -20,13 +28,53 @@ pub struct Client {
 }
 
 impl Client {
+    /// Client backed by the DynamoDB table `table_name`
     pub fn new(table_name: &str, sdk_config: &SdkConfig) -> Self {
+        Self::from_store(DynamoStore::new(table_name, sdk_config))
+    }
+    /// Client backed by an in-memory store, every item is lost when the client is dropped
+    pub fn in_memory() -> Self {
+        Self::from_store(MemoryStore::new())
+    }
+    /// Client backed by the SQLite database at `path`
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(path: impl AsRef<std::path::Path>) -> error::Result<Self> {
+        Ok(Self::from_store(store::SqliteStore::open(path)?))
+    }
+    pub fn from_store(store: impl LpStore + 'static) -> Self {
         let handle = Arc::new(Handle {
-            inner: DynamoDbClient::new(sdk_config),
-            table_name: table_name.to_string(),
+            store: Arc::new(store),
+            seasons: season::SeasonCalendar::default(),
+            retention: None,
         });
         Self { handle }
     }
+    /// Tag snapshots with the splits of `seasons` and keep history queries within a split
+    pub fn with_seasons(self, seasons: season::SeasonCalendar) -> Self {
+        let handle = Arc::new(Handle {
+            store: self.handle.store.clone(),
+            seasons,
+            retention: self.handle.retention,
+        });
+        Self { handle }
+    }
+    /// Expire the snapshots written after `policy`'s maximum age and downsample the old ones with
+    /// [`builders::league_points::LeaguePointClient::apply_retention`]
+    pub fn with_retention(
+        self,
+        policy: builders::league_points::retention::RetentionPolicy,
+    ) -> error::Result<Self> {
+        policy.validate()?;
+        let handle = Arc::new(Handle {
+            store: self.handle.store.clone(),
+            seasons: self.handle.seasons.clone(),
+            retention: Some(policy),
+        });
+        Ok(Self { handle })
+    }
+    pub fn seasons(&self) -> &season::SeasonCalendar {
+        &self.handle.seasons
+    }
 
     pub fn tracking(&self, game_type: GameType) -> builders::tracking::TrackingClient {
         builders::tracking::TrackingClient::new(self.handle.clone(), game_type)
@@ -34,6 +82,31 @@ impl Client {
     pub fn league_points(&self, game_type: GameType) -> builders::league_points::LeaguePointClient {
         builders::league_points::LeaguePointClient::new(self.handle.clone(), game_type)
     }
+    /// The audit log of the tracking and configuration changes of the guilds
+    pub fn audit(&self) -> builders::audit::AuditClient {
+        builders::audit::AuditClient::new(self.handle.clone())
+    }
+    pub fn profiles(&self) -> builders::profile::ProfileClient {
+        builders::profile::ProfileClient::new(self.handle.clone())
+    }
+    /// Collect the tracking rows, league points history and profiles to export
+    pub fn export(&self) -> export::ExportBuilder {
+        export::ExportBuilder::new(self.handle.clone())
+    }
+    /// Restore the records of an export, e.g. read from another table's export
+    pub fn import(&self, export: export::Export) -> export::ImportBuilder {
+        export::ImportBuilder::new(self.handle.clone(), export)
+    }
+    pub fn match_cache(&self, game_type: GameType) -> cache::MatchCache {
+        cache::MatchCache::new(self.handle.clone(), game_type)
+    }
+    pub fn schema_version(&self) -> migration::SchemaVersionBuilder {
+        migration::SchemaVersionBuilder::new(self.handle.clone())
+    }
+    /// Bring the layout of the stored items up to date, see [`migration::migrations`]
+    pub fn migrate(&self) -> migration::MigrateBuilder {
+        migration::MigrateBuilder::new(self.handle.clone())
+    }
 }
 
 fn ident(game_type: GameType) -> &'static str {
@@ -42,13 +115,76 @@ fn ident(game_type: GameType) -> &'static str {
         GameType::Tft => "TFT#",
     }
 }
-#[derive(Debug, Default, Clone, Copy)]
+/// Reject a queue of another game than the client's
+fn check_queue(game_type: GameType, queue: Queue) -> error::Result<()> {
+    match queue.game_type() == game_type {
+        true => Ok(()),
+        false => Err(error::Error::Validation(format!(
+            "{:?} is not a {:?} queue",
+            queue, game_type
+        ))),
+    }
+}
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GameType {
     #[default]
     League,
     Tft,
 }
 
+/// Ranked queue of a league points snapshot, serialized as the Riot queue type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Queue {
+    #[serde(rename = "RANKED_SOLO_5x5")]
+    Solo,
+    #[serde(rename = "RANKED_FLEX_SR")]
+    Flex,
+    #[serde(rename = "RANKED_TFT")]
+    TftRanked,
+    #[serde(rename = "RANKED_TFT_TURBO")]
+    HyperRoll,
+    #[serde(rename = "RANKED_TFT_DOUBLE_UP")]
+    DoubleUp,
+}
+
+impl Queue {
+    pub const ALL: [Queue; 5] = [
+        Queue::Solo,
+        Queue::Flex,
+        Queue::TftRanked,
+        Queue::HyperRoll,
+        Queue::DoubleUp,
+    ];
+    pub fn game_type(self) -> GameType {
+        match self {
+            Queue::Solo | Queue::Flex => GameType::League,
+            Queue::TftRanked | Queue::HyperRoll | Queue::DoubleUp => GameType::Tft,
+        }
+    }
+    /// The queue of the snapshots stored before snapshots had a queue
+    pub fn default_for(game_type: GameType) -> Self {
+        match game_type {
+            GameType::League => Queue::Solo,
+            GameType::Tft => Queue::TftRanked,
+        }
+    }
+    /// The `queueType` of the Riot league entries
+    pub fn riot_queue_type(self) -> &'static str {
+        match self {
+            Queue::Solo => "RANKED_SOLO_5x5",
+            Queue::Flex => "RANKED_FLEX_SR",
+            Queue::TftRanked => "RANKED_TFT",
+            Queue::HyperRoll => "RANKED_TFT_TURBO",
+            Queue::DoubleUp => "RANKED_TFT_DOUBLE_UP",
+        }
+    }
+    pub fn from_riot_queue_type(queue_type: &str) -> Option<Self> {
+        Queue::ALL
+            .into_iter()
+            .find(|queue| queue.riot_queue_type() == queue_type)
+    }
+}
+
 impl Clone for Client {
     fn clone(&self) -> Self {
         Self {
@@ -57,7 +193,7 @@ impl Clone for Client {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ItemModel {
     /// PK
     pub id: String,
@@ -65,13 +201,6 @@ pub struct ItemModel {
     pub sk: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct TrackingModel {
-    #[serde(flatten)]
-    pub item: ItemModel,
-
-    pub ids: Vec<RiotAccountDetails>,
-}
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RiotAccountDetails {
@@ -80,3 +209,76 @@ pub struct RiotAccountDetails {
     puuid: String,
     summoner_name: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[tokio::test]
+    async fn queues() {
+        let client = Client::in_memory();
+        let league_points = client.league_points(GameType::League);
+        for (queue, league_points_value) in [(Queue::Solo, 10), (Queue::Flex, 20)] {
+            league_points
+                .add()
+                .puuid("puuid")
+                .queue(queue)
+                .timestamp(100)
+                .tier("GOLD")
+                .rank("II")
+                .league_points(league_points_value)
+                .wins(10)
+                .losses(5)
+                .send()
+                .await
+                .unwrap();
+        }
+        let between = |queue| {
+            league_points
+                .get_between()
+                .puuid("puuid")
+                .queue(queue)
+                .start_time(0)
+                .end_time(i64::MAX)
+                .send()
+        };
+        let solo = between(Queue::Solo).await.unwrap().unwrap();
+        assert_eq!(solo.len(), 1);
+        assert_eq!(solo[0].league_points, 10);
+        let flex = between(Queue::Flex).await.unwrap().unwrap();
+        assert_eq!(flex.len(), 1);
+        assert_eq!(flex[0].league_points, 20);
+        assert!(matches!(
+            between(Queue::HyperRoll).await,
+            Err(Error::Validation(_))
+        ));
+
+        let tracking = client.tracking(GameType::Tft);
+        tracking
+            .track_user()
+            .guild_id("guild")
+            .puuid("puuid")
+            .account_id("account")
+            .summoner_name("monke")
+            .queues(&[Queue::TftRanked, Queue::HyperRoll])
+            .send()
+            .await
+            .unwrap();
+        let tracked = tracking.list().send().await.unwrap();
+        assert_eq!(
+            tracked[0].tracked_queues(),
+            vec![Queue::TftRanked, Queue::HyperRoll]
+        );
+        let res = tracking
+            .track_user()
+            .guild_id("guild")
+            .puuid("puuid")
+            .account_id("account")
+            .summoner_name("monke")
+            .queues(&[Queue::Flex])
+            .send()
+            .await;
+        assert!(matches!(res, Err(Error::Validation(_))));
+    }
+}
@@ -0,0 +1,364 @@
+//! Versioning of the layout of the stored items
+//!
+//! The sort keys encode the layout of the table (`#LEAGUE#<ts>`, `SUMMONER#TFT#<id>`...), so
+//! changing them leaves the existing rows unreadable. Every layout change is a [`Migration`],
+//! and the version of the last one applied is kept in a marker item of the table.
+use crate::builders::league_points::between::GetBetweenBuilder;
+use crate::builders::league_points::day_key;
+use crate::builders::tracking::migrate::MigrateToPuuidBuilder;
+use crate::builders::tracking::TrackingItem;
+use crate::error::{Error, Result};
+use crate::store::paginate;
+use crate::{Client, GameType, Handle, ItemModel, Queue};
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::stream::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// The version of the layout written by this crate, the version of the last of [`migrations`]
+pub const LATEST_VERSION: u32 = 2;
+
+/// A change of the layout of the stored items
+///
+/// Migrations are applied in increasing version order, each one at most once per table.
+#[async_trait]
+pub trait Migration: Send + Sync {
+    /// The schema version once applied, starting at 1
+    fn version(&self) -> u32;
+    fn description(&self) -> &str;
+    /// Returns the number of items changed, or that would be changed when `dry_run`
+    async fn apply(&self, client: &Client, dry_run: bool) -> Result<usize>;
+}
+
+/// The migrations of the layouts written by previous versions of this crate, in order
+pub fn migrations() -> Vec<Box<dyn Migration>> {
+    vec![Box::new(PuuidKeys), Box::new(DayIndex)]
+}
+
+/// Key the tracking items and league points history by PUUID instead of summoner id, see
+/// [`MigrateToPuuidBuilder`]
+///
+/// The stored PUUIDs are kept, PUUIDs resolved with another API key than the one of their game
+/// need [`MigrateToPuuidBuilder::resolver`].
+struct PuuidKeys;
+
+#[async_trait]
+impl Migration for PuuidKeys {
+    fn version(&self) -> u32 {
+        1
+    }
+    fn description(&self) -> &str {
+        "Key the tracking items and league points snapshots by PUUID"
+    }
+    async fn apply(&self, client: &Client, dry_run: bool) -> Result<usize> {
+        let mut changed = 0;
+        for game_type in [GameType::League, GameType::Tft] {
+            let migration = MigrateToPuuidBuilder::new(client.handle.clone(), game_type)
+                .dry_run(dry_run)
+                .send()
+                .await?;
+            changed += migration.tracking + migration.snapshots;
+        }
+        Ok(changed)
+    }
+}
+
+/// Set the `byDay` index key of the snapshots written before the index existed
+///
+/// Players are found through the tracking rows, so the history of a player no longer tracked
+/// is left out of the index.
+struct DayIndex;
+
+#[async_trait]
+impl Migration for DayIndex {
+    fn version(&self) -> u32 {
+        2
+    }
+    fn description(&self) -> &str {
+        "Add the league points snapshots to the byDay index"
+    }
+    async fn apply(&self, client: &Client, dry_run: bool) -> Result<usize> {
+        let mut changed = 0;
+        for game_type in [GameType::League, GameType::Tft] {
+            let handle = client.handle.clone();
+            let puuids: BTreeSet<String> = paginate(move |start| {
+                let handle = handle.clone();
+                async move { handle.store.list_tracked(game_type, None, start).await }
+            })
+            .map_ok(|item: TrackingItem| item.puuid)
+            .try_collect()
+            .await?;
+            for puuid in &puuids {
+                for queue in Queue::ALL
+                    .into_iter()
+                    .filter(|queue| queue.game_type() == game_type)
+                {
+                    let mut snapshots = GetBetweenBuilder::new(client.handle.clone(), game_type)
+                        .puuid(puuid)
+                        .queue(queue)
+                        .start_time(0)
+                        .end_time(i64::MAX)
+                        .send()
+                        .await?
+                        .unwrap_or_default();
+                    snapshots.retain(|snapshot| snapshot.day.is_none());
+                    changed += snapshots.len();
+                    if dry_run || snapshots.is_empty() {
+                        continue;
+                    }
+                    for snapshot in &mut snapshots {
+                        snapshot.day = day_key(game_type, snapshot.timestamp().timestamp());
+                    }
+                    client.handle.store.add_many(snapshots).await?;
+                }
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// The schema version marker, version 0 for tables never migrated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaVersion {
+    #[serde(flatten)]
+    /// The item partition key
+    pub item: ItemModel,
+    pub version: u32,
+    /// The migrations applied to the table, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub applied: Vec<AppliedMigration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub description: String,
+    /// When the migration was applied, or planned for a dry run
+    pub applied_at: i64,
+    /// The number of items changed
+    pub items: usize,
+}
+
+fn schema_key() -> ItemModel {
+    ItemModel {
+        id: "SCHEMA".to_string(),
+        sk: "VERSION".to_string(),
+    }
+}
+
+async fn get_schema_version(handle: &Handle) -> Result<SchemaVersion> {
+    let items = handle.store.get_items(&[schema_key()]).await?;
+    match items.into_iter().next() {
+        Some(item) => Ok(serde_json::from_value(item)?),
+        None => Ok(SchemaVersion {
+            item: schema_key(),
+            version: 0,
+            applied: Vec::new(),
+        }),
+    }
+}
+
+/// Get the schema version of the table
+pub struct SchemaVersionBuilder {
+    handle: Arc<Handle>,
+}
+
+impl SchemaVersionBuilder {
+    pub fn new(handle: Arc<Handle>) -> Self {
+        Self { handle }
+    }
+    pub async fn send(self) -> Result<SchemaVersion> {
+        get_schema_version(&self.handle).await
+    }
+}
+
+/// Outcome of [`MigrateBuilder`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// The schema version before migrating
+    pub from: u32,
+    /// The schema version after migrating, or that would be reached for a dry run
+    pub to: u32,
+    pub dry_run: bool,
+    /// The migrations applied, or pending for a dry run
+    pub migrations: Vec<AppliedMigration>,
+}
+
+/// Apply the migrations newer than the schema version of the table
+///
+/// The schema version is updated after each migration, so a failed run resumes from the
+/// migration that failed.
+pub struct MigrateBuilder {
+    handle: Arc<Handle>,
+    migrations: Vec<Box<dyn Migration>>,
+    /// Stop at this version, the latest migration when unset
+    target: Option<u32>,
+    /// Only report the pending migrations and the items they would change
+    dry_run: bool,
+}
+
+impl MigrateBuilder {
+    pub fn new(handle: Arc<Handle>) -> Self {
+        Self {
+            handle,
+            migrations: migrations(),
+            target: None,
+            dry_run: false,
+        }
+    }
+    /// Add a migration to the ones of this crate
+    pub fn migration(mut self, migration: impl Migration + 'static) -> Self {
+        self.migrations.push(Box::new(migration));
+        self
+    }
+    pub fn target(mut self, version: u32) -> Self {
+        self.target = Some(version);
+        self
+    }
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+    pub async fn send(mut self) -> Result<MigrationReport> {
+        self.migrations.sort_by_key(|migration| migration.version());
+        for (i, migration) in self.migrations.iter().enumerate() {
+            if migration.version() == 0
+                || i > 0 && self.migrations[i - 1].version() == migration.version()
+            {
+                return Err(Error::Validation(format!(
+                    "Invalid or duplicate migration version {}",
+                    migration.version()
+                )));
+            }
+        }
+        let mut schema = get_schema_version(&self.handle).await?;
+        let from = schema.version;
+        let target = self
+            .target
+            .or_else(|| self.migrations.last().map(|migration| migration.version()))
+            .unwrap_or(from);
+        if target < from {
+            return Err(Error::Validation(format!(
+                "Schema version {} is newer than the target {}, migrations cannot be reverted",
+                from, target
+            )));
+        }
+
+        let client = Client {
+            handle: self.handle.clone(),
+        };
+        let mut report = MigrationReport {
+            from,
+            to: from,
+            dry_run: self.dry_run,
+            migrations: Vec::new(),
+        };
+        for migration in self
+            .migrations
+            .iter()
+            .filter(|migration| from < migration.version() && migration.version() <= target)
+        {
+            let items = migration.apply(&client, self.dry_run).await?;
+            let applied = AppliedMigration {
+                version: migration.version(),
+                description: migration.description().to_string(),
+                applied_at: Utc::now().timestamp(),
+                items,
+            };
+            report.to = applied.version;
+            report.migrations.push(applied.clone());
+            if self.dry_run {
+                continue;
+            }
+            schema.version = applied.version;
+            schema.applied.push(applied);
+            let item = serde_json::to_value(&schema)?;
+            self.handle.store.put_items(vec![item]).await?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{LpStore, MemoryStore};
+
+    #[tokio::test]
+    async fn schema_migrations() {
+        struct Noop(u32);
+        #[async_trait::async_trait]
+        impl Migration for Noop {
+            fn version(&self) -> u32 {
+                self.0
+            }
+            fn description(&self) -> &str {
+                "Noop"
+            }
+            async fn apply(&self, _: &Client, _: bool) -> crate::error::Result<usize> {
+                Ok(0)
+            }
+        }
+
+        let store = MemoryStore::new();
+        let tracked = r#"{"id":"TRACKING#guild","sk":"SUMMONER#LEAGUE#summoner","puuid":"puuid","accountId":"account","summonerName":"monke","guildId":"guild"}"#;
+        let tracked: serde_json::Value = serde_json::from_str(tracked).unwrap();
+        let mut shared = tracked.clone();
+        shared["id"] = "TRACKING".into();
+        shared["sk"] = "SUMMONER#LEAGUE#summoner#GUILD#guild".into();
+        store.put_items(vec![tracked, shared]).await.unwrap();
+        let client = Client::from_store(store);
+        client
+            .league_points(GameType::League)
+            .add()
+            .puuid("summoner")
+            .timestamp(1704085200)
+            .tier("GOLD")
+            .rank("II")
+            .league_points(50)
+            .wins(10)
+            .losses(5)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(client.schema_version().send().await.unwrap().version, 0);
+
+        let report = client.migrate().dry_run(true).send().await.unwrap();
+        assert_eq!((report.from, report.to), (0, LATEST_VERSION));
+        assert_eq!(report.migrations[0].items, 2);
+        assert_eq!(client.schema_version().send().await.unwrap().version, 0);
+        let tracking = client.tracking(GameType::League);
+        let tracked = tracking.list().guild_id("guild").send().await.unwrap();
+        assert_eq!(tracked[0].key_id(), "summoner");
+
+        let report = client.migrate().send().await.unwrap();
+        assert_eq!(report.migrations.len(), 2);
+        let schema = client.schema_version().send().await.unwrap();
+        assert_eq!(schema.version, LATEST_VERSION);
+        assert_eq!(schema.applied, report.migrations);
+        let tracked = tracking.list().guild_id("guild").send().await.unwrap();
+        assert_eq!(tracked[0].key_id(), "puuid");
+        assert!(client.migrate().send().await.unwrap().migrations.is_empty());
+
+        let next = LATEST_VERSION + 1;
+        let report = client.migrate().migration(Noop(next)).send().await.unwrap();
+        assert_eq!(report.to, next);
+        assert!(matches!(
+            client.migrate().target(0).send().await,
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            client
+                .migrate()
+                .migration(Noop(next + 1))
+                .migration(Noop(next + 1))
+                .send()
+                .await,
+            Err(Error::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+//! Ranked seasons and splits, the league points and ranks of the players reset between splits
+use crate::error::{Error, Result};
+use crate::GameType;
+use serde::{Deserialize, Serialize};
+
+/// A ranked split, running until the start of the next split of the same game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Split {
+    /// e.g. `2024`
+    pub season: String,
+    /// Number of the split in its season, starting at 1
+    pub split: u32,
+    /// Timestamp of the ranked reset starting the split
+    pub start: i64,
+}
+
+impl Split {
+    /// Identifier of the split, as `<season>-S<split>`
+    pub fn id(&self) -> String {
+        format!("{}-S{}", self.season, self.split)
+    }
+}
+
+/// The ranked splits of each game
+///
+/// Deserializes from `{"league": [<split>...], "tft": [<split>...]}`, splits in any order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeasonCalendar {
+    #[serde(default)]
+    pub league: Vec<Split>,
+    #[serde(default)]
+    pub tft: Vec<Split>,
+}
+
+impl SeasonCalendar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The calendar configured as JSON in the `SEASON_CALENDAR` environment variable, empty when
+    /// it is not set
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("SEASON_CALENDAR") {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                Error::Validation(format!("SEASON_CALENDAR is not a season calendar: {}", e))
+            }),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+    pub fn split(mut self, game_type: GameType, season: &str, split: u32, start: i64) -> Self {
+        let split = Split {
+            season: season.to_string(),
+            split,
+            start,
+        };
+        match game_type {
+            GameType::League => self.league.push(split),
+            GameType::Tft => self.tft.push(split),
+        }
+        self
+    }
+    pub fn splits(&self, game_type: GameType) -> &[Split] {
+        match game_type {
+            GameType::League => &self.league,
+            GameType::Tft => &self.tft,
+        }
+    }
+    /// The split running at `timestamp`, `None` before the first split of the calendar
+    pub fn split_at(&self, game_type: GameType, timestamp: i64) -> Option<&Split> {
+        self.splits(game_type)
+            .iter()
+            .filter(|split| split.start <= timestamp)
+            .max_by_key(|split| split.start)
+    }
+    /// The split with the identifier `id`, see [`Split::id`]
+    pub fn find(&self, game_type: GameType, id: &str) -> Option<&Split> {
+        self.splits(game_type).iter().find(|split| split.id() == id)
+    }
+    /// The first and last timestamps of a split, the last one is `i64::MAX` for the current split
+    pub fn bounds(&self, game_type: GameType, split: &Split) -> (i64, i64) {
+        let end = self
+            .splits(game_type)
+            .iter()
+            .map(|next| next.start)
+            .filter(|start| *start > split.start)
+            .min()
+            .map_or(i64::MAX, |start| start - 1);
+        (split.start, end)
+    }
+    /// Narrow `(start, end)` to the split running at `timestamp`
+    ///
+    /// The bounds are unchanged when the calendar has no split at `timestamp`.
+    pub(crate) fn clamp(
+        &self,
+        game_type: GameType,
+        timestamp: i64,
+        (start, end): (i64, i64),
+    ) -> (i64, i64) {
+        match self.split_at(game_type, timestamp) {
+            Some(split) => {
+                let (split_start, split_end) = self.bounds(game_type, split);
+                (start.max(split_start), end.min(split_end))
+            }
+            None => (start, end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+
+    #[tokio::test]
+    async fn seasons_and_splits() {
+        const T: i64 = 1704085200;
+        const RESET: i64 = T + 10 * 86400;
+        let seasons = SeasonCalendar::new()
+            .split(GameType::League, "2024", 2, RESET)
+            .split(GameType::League, "2024", 1, T - 86400);
+        let client = Client::in_memory().with_seasons(seasons);
+        let league_points = client.league_points(GameType::League);
+        let add = |timestamp, tier: &str, rank: &str, league_points, wins| {
+            client
+                .league_points(GameType::League)
+                .add()
+                .puuid("puuid")
+                .timestamp(timestamp)
+                .tier(tier)
+                .rank(rank)
+                .league_points(league_points)
+                .wins(wins)
+                .losses(10)
+        };
+        add(T, "GOLD", "II", 50, 10).send().await.unwrap();
+        add(T + 86400, "PLATINUM", "IV", 10, 15)
+            .send()
+            .await
+            .unwrap();
+        add(T + 2 * 86400, "GOLD", "I", 90, 20)
+            .send()
+            .await
+            .unwrap();
+        add(RESET + 3600, "SILVER", "I", 0, 1).send().await.unwrap();
+
+        let history = league_points.history("puuid", ..).send().await.unwrap();
+        let splits: Vec<_> = history.iter().map(|lp| lp.split.as_deref()).collect();
+        assert_eq!(
+            splits,
+            vec![
+                Some("2024-S1"),
+                Some("2024-S1"),
+                Some("2024-S1"),
+                Some("2024-S2")
+            ]
+        );
+
+        // The first snapshot of the day of the reset is not compared across splits
+        let after_reset = league_points
+            .history("puuid", T + 2 * 86400..)
+            .within_split_of(RESET + 7200)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(after_reset.len(), 1);
+        let before = league_points
+            .at_or_before("puuid", RESET + 7200)
+            .within_split_of(RESET - 1)
+            .send()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(before.tier, "GOLD");
+        let none = league_points
+            .history("puuid", ..T + 86400)
+            .within_split_of(RESET)
+            .send()
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+    }
+}
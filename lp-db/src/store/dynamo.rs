@@ -0,0 +1,539 @@
+use super::{BetweenQuery, DayQuery, ItemQuery, LpStore, Page, PageToken};
+use crate::builders::league_points::{lp_sk, LatestStanding, LpItem};
+use crate::builders::tracking::TrackingItem;
+use crate::builders::tracking::{tracked_by_prefix, tracking_partition, tracking_sk};
+use crate::error::{Error, Result};
+use crate::{ident, GameType, ItemModel, Queue};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_dynamodb::error::SdkError;
+use aws_sdk_dynamodb::operation::put_item::PutItemError;
+use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsError;
+use aws_sdk_dynamodb::types::{
+    AttributeValue, Delete, DeleteRequest, KeysAndAttributes, Put, PutRequest, TransactWriteItem,
+    WriteRequest,
+};
+use aws_sdk_dynamodb::Client as DynamoDbClient;
+use serde_dynamo::aws_sdk_dynamodb_0_25::{from_item, from_items, to_item};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Most items of a `BatchWriteItem` request
+const BATCH_WRITE_SIZE: usize = 25;
+/// Most keys of a `BatchGetItem` request
+const BATCH_GET_SIZE: usize = 100;
+/// Global secondary index of the snapshots keyed by `day` and `sk`
+const DAY_INDEX: &str = "byDay";
+/// Retries of the items left unprocessed by a batch request, usually because of throttling
+const BATCH_RETRIES: u32 = 5;
+
+/// [`LpStore`] backed by the DynamoDB table of the LP service
+#[derive(Debug, Clone)]
+pub struct DynamoStore {
+    pub inner: DynamoDbClient,
+    pub table_name: String,
+}
+
+impl DynamoStore {
+    pub fn new(table_name: &str, sdk_config: &SdkConfig) -> Self {
+        Self {
+            inner: DynamoDbClient::new(sdk_config),
+            table_name: table_name.to_string(),
+        }
+    }
+}
+
+type Item = HashMap<String, AttributeValue>;
+
+impl DynamoStore {
+    /// Write a tracking item, its copy in the shared list and the `audit` item in one transaction,
+    /// failing with [`Error::AlreadyTracked`] when `condition` on the item of the guild is not met
+    async fn transact_track(
+        &self,
+        item: TrackingItem,
+        condition: Option<&str>,
+        audit: Option<Value>,
+    ) -> Result<()> {
+        let shared = item.shared();
+        let put = |item: Item, condition: Option<&str>| {
+            let put = Put::builder()
+                .table_name(self.table_name.as_str())
+                .set_item(Some(item))
+                .set_condition_expression(condition.map(str::to_string))
+                .build();
+            TransactWriteItem::builder().put(put).build()
+        };
+        let mut request = self
+            .inner
+            .transact_write_items()
+            .transact_items(put(to_item(&item)?, condition))
+            .transact_items(put(to_item(&shared)?, None));
+        if let Some(audit) = audit {
+            request = request.transact_items(put(to_item(audit)?, None));
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| match cancelled_by_condition(&e) {
+                true => Error::AlreadyTracked,
+                false => e.into(),
+            })?;
+        Ok(())
+    }
+    async fn batch_put(&self, items: Vec<Item>) -> Result<()> {
+        let requests = items
+            .into_iter()
+            .map(|item| {
+                let put = PutRequest::builder().set_item(Some(item)).build();
+                WriteRequest::builder().put_request(put).build()
+            })
+            .collect();
+        self.batch_write(requests).await
+    }
+    /// Send write requests in `BatchWriteItem` requests of 25, retrying the unprocessed ones
+    async fn batch_write(&self, requests: Vec<WriteRequest>) -> Result<()> {
+        for chunk in requests.chunks(BATCH_WRITE_SIZE) {
+            let mut pending = chunk.to_vec();
+            let mut attempt = 0;
+            while !pending.is_empty() {
+                backoff(attempt, pending.len()).await?;
+                let res = self
+                    .inner
+                    .batch_write_item()
+                    .request_items(self.table_name.as_str(), pending)
+                    .send()
+                    .await?;
+                pending = res
+                    .unprocessed_items
+                    .and_then(|mut unprocessed| unprocessed.remove(&self.table_name))
+                    .unwrap_or_default();
+                attempt += 1;
+            }
+        }
+        Ok(())
+    }
+    /// Get items with `BatchGetItem` requests of 100 keys, retrying the unprocessed keys
+    ///
+    /// Items are returned in any order, missing items are skipped.
+    async fn batch_get(&self, keys: Vec<Item>) -> Result<Vec<Item>> {
+        let mut items = Vec::new();
+        for chunk in keys.chunks(BATCH_GET_SIZE) {
+            let mut pending = chunk.to_vec();
+            let mut attempt = 0;
+            while !pending.is_empty() {
+                backoff(attempt, pending.len()).await?;
+                let keys = KeysAndAttributes::builder().set_keys(Some(pending)).build();
+                let res = self
+                    .inner
+                    .batch_get_item()
+                    .request_items(self.table_name.as_str(), keys)
+                    .send()
+                    .await?;
+                items.extend(
+                    res.responses
+                        .and_then(|mut responses| responses.remove(&self.table_name))
+                        .unwrap_or_default(),
+                );
+                pending = res
+                    .unprocessed_keys
+                    .and_then(|mut unprocessed| unprocessed.remove(&self.table_name))
+                    .and_then(|unprocessed| unprocessed.keys)
+                    .unwrap_or_default();
+                attempt += 1;
+            }
+        }
+        Ok(items)
+    }
+}
+
+#[async_trait]
+impl LpStore for DynamoStore {
+    async fn track(&self, item: TrackingItem) -> Result<()> {
+        self.transact_track(item, None, None).await
+    }
+    async fn track_new(&self, item: TrackingItem, audit: Option<Value>) -> Result<()> {
+        self.transact_track(item, Some("attribute_not_exists(id)"), audit)
+            .await
+    }
+    async fn untrack(
+        &self,
+        game_type: GameType,
+        guild_id: Option<&str>,
+        puuid: &str,
+        audit: Option<Value>,
+    ) -> Result<()> {
+        let mut keys = vec![(
+            tracking_partition(guild_id),
+            tracking_sk(game_type, puuid, None),
+        )];
+        if guild_id.is_some() {
+            keys.push((
+                tracking_partition(None),
+                tracking_sk(game_type, puuid, guild_id),
+            ));
+        }
+        let mut request = self.inner.transact_write_items();
+        for (i, (id, sk)) in keys.into_iter().enumerate() {
+            // The copy in the shared list is written along with the item of the guild
+            let condition = (i == 0).then_some("attribute_exists(id)");
+            let delete = Delete::builder()
+                .table_name(self.table_name.as_str())
+                .key("id", AttributeValue::S(id))
+                .key("sk", AttributeValue::S(sk))
+                .set_condition_expression(condition.map(str::to_string))
+                .build();
+            request = request.transact_items(TransactWriteItem::builder().delete(delete).build());
+        }
+        if let Some(audit) = audit {
+            let put = Put::builder()
+                .table_name(self.table_name.as_str())
+                .set_item(Some(to_item(audit)?))
+                .build();
+            request = request.transact_items(TransactWriteItem::builder().put(put).build());
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| match cancelled_by_condition(&e) {
+                true => Error::NotTracked,
+                false => e.into(),
+            })?;
+        Ok(())
+    }
+    async fn list_tracked(
+        &self,
+        game_type: GameType,
+        guild_id: Option<&str>,
+        start: Option<PageToken>,
+    ) -> Result<Page<TrackingItem>> {
+        let ident = ident(game_type);
+        let res = self
+            .inner
+            .query()
+            .table_name(self.table_name.as_str())
+            .key_condition_expression("id = :id AND begins_with(sk, :sk)")
+            .expression_attribute_values(":id", AttributeValue::S(tracking_partition(guild_id)))
+            .expression_attribute_values(":sk", AttributeValue::S(format!("SUMMONER#{}", ident)))
+            .set_exclusive_start_key(start.map(start_key))
+            .send()
+            .await?;
+
+        page(res.items, res.last_evaluated_key)
+    }
+    async fn list_tracked_by(
+        &self,
+        game_type: GameType,
+        puuid: &str,
+        start: Option<PageToken>,
+    ) -> Result<Page<TrackingItem>> {
+        let res = self
+            .inner
+            .query()
+            .table_name(self.table_name.as_str())
+            .key_condition_expression("id = :id AND begins_with(sk, :sk)")
+            .expression_attribute_values(":id", AttributeValue::S(tracking_partition(None)))
+            .expression_attribute_values(
+                ":sk",
+                AttributeValue::S(tracked_by_prefix(game_type, puuid)),
+            )
+            .set_exclusive_start_key(start.map(start_key))
+            .send()
+            .await?;
+
+        page(res.items, res.last_evaluated_key)
+    }
+    async fn add(&self, item: LpItem) -> Result<()> {
+        self.inner
+            .put_item()
+            .table_name(self.table_name.as_str())
+            .set_item(Some(to_item(&item)?))
+            .send()
+            .await?;
+        Ok(())
+    }
+    async fn add_unless_unchanged(&self, item: LpItem, since: i64) -> Result<bool> {
+        let standing = LatestStanding::of(&item)
+            .ok_or_else(|| Error::Validation(format!("{} is not a snapshot", item.item.sk)))?;
+        let standing: Item = to_item(&standing)?;
+        // Written when the standing is missing, older than `since` or different
+        let mut condition = vec![
+            "attribute_not_exists(id)".to_string(),
+            "#timestamp < :since".to_string(),
+        ];
+        let mut names = HashMap::from([("#timestamp".to_string(), "timestamp".to_string())]);
+        let mut values =
+            HashMap::from([(":since".to_string(), AttributeValue::N(since.to_string()))]);
+        for field in ["tier", "rank", "leaguePoints", "wins", "losses"] {
+            let value = standing
+                .get(field)
+                .cloned()
+                .ok_or_else(|| Error::missing(field))?;
+            condition.push(format!("#{} <> :{}", field, field));
+            names.insert(format!("#{}", field), field.to_string());
+            values.insert(format!(":{}", field), value);
+        }
+        let standing = Put::builder()
+            .table_name(self.table_name.as_str())
+            .set_item(Some(standing))
+            .condition_expression(condition.join(" OR "))
+            .set_expression_attribute_names(Some(names))
+            .set_expression_attribute_values(Some(values))
+            .build();
+        let snapshot = Put::builder()
+            .table_name(self.table_name.as_str())
+            .set_item(Some(to_item(&item)?))
+            .build();
+        let res = self
+            .inner
+            .transact_write_items()
+            .transact_items(TransactWriteItem::builder().put(standing).build())
+            .transact_items(TransactWriteItem::builder().put(snapshot).build())
+            .send()
+            .await;
+        match res {
+            Ok(_) => Ok(true),
+            Err(e) if cancelled_by_condition(&e) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+    async fn add_many(&self, items: Vec<LpItem>) -> Result<()> {
+        let items = items
+            .iter()
+            .map(to_item)
+            .collect::<std::result::Result<_, _>>()?;
+        self.batch_put(items).await
+    }
+    async fn get_many(
+        &self,
+        queue: Queue,
+        puuids: &[String],
+        timestamp: i64,
+    ) -> Result<Vec<LpItem>> {
+        let keys = puuids
+            .iter()
+            .map(|puuid| key(puuid.clone(), lp_sk(queue, timestamp)))
+            .collect();
+        Ok(from_items(self.batch_get(keys).await?)?)
+    }
+    async fn put_items(&self, items: Vec<Value>) -> Result<()> {
+        let items = items
+            .iter()
+            .map(to_item)
+            .collect::<std::result::Result<_, _>>()?;
+        self.batch_put(items).await
+    }
+    async fn put_versioned(&self, item: Value, previous: Option<u64>) -> Result<bool> {
+        let condition = match previous {
+            None => "attribute_not_exists(id)",
+            Some(0) => "attribute_exists(id) AND attribute_not_exists(#version)",
+            Some(_) => "#version = :version",
+        };
+        let mut request = self
+            .inner
+            .put_item()
+            .table_name(self.table_name.as_str())
+            .set_item(Some(to_item(item)?))
+            .condition_expression(condition);
+        if previous.is_some() {
+            request = request.expression_attribute_names("#version", "version");
+        }
+        if let Some(previous @ 1..) = previous {
+            request = request
+                .expression_attribute_values(":version", AttributeValue::N(previous.to_string()));
+        }
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(SdkError::ServiceError(context))
+                if matches!(
+                    context.err(),
+                    PutItemError::ConditionalCheckFailedException(_)
+                ) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+    async fn get_items(&self, keys: &[ItemModel]) -> Result<Vec<Value>> {
+        let keys = keys
+            .iter()
+            .map(|item| key(item.id.clone(), item.sk.clone()))
+            .collect();
+        Ok(from_items(self.batch_get(keys).await?)?)
+    }
+    async fn remove_items(&self, keys: &[ItemModel]) -> Result<()> {
+        let requests = keys
+            .iter()
+            .map(|item| {
+                let delete = DeleteRequest::builder()
+                    .set_key(Some(key(item.id.clone(), item.sk.clone())))
+                    .build();
+                WriteRequest::builder().delete_request(delete).build()
+            })
+            .collect();
+        self.batch_write(requests).await
+    }
+    async fn remove(&self, queue: Queue, puuid: &str, timestamp: i64) -> Result<()> {
+        self.inner
+            .delete_item()
+            .table_name(self.table_name.as_str())
+            .key("id", AttributeValue::S(puuid.to_string()))
+            .key("sk", AttributeValue::S(lp_sk(queue, timestamp)))
+            .send()
+            .await?;
+        Ok(())
+    }
+    async fn get(&self, queue: Queue, puuid: &str, timestamp: i64) -> Result<Option<LpItem>> {
+        let res = self
+            .inner
+            .get_item()
+            .table_name(self.table_name.as_str())
+            .key("id", AttributeValue::S(puuid.to_string()))
+            .key("sk", AttributeValue::S(lp_sk(queue, timestamp)))
+            .send()
+            .await?;
+
+        match res.item {
+            Some(item) => Ok(Some(from_item(item)?)),
+            None => Ok(None),
+        }
+    }
+    async fn get_between(
+        &self,
+        query: &BetweenQuery,
+        start: Option<PageToken>,
+    ) -> Result<Page<LpItem>> {
+        let res = self
+            .inner
+            .query()
+            .table_name(self.table_name.as_str())
+            .key_condition_expression("id = :id AND sk BETWEEN :start_time AND :end_time")
+            .expression_attribute_values(":id", AttributeValue::S(query.puuid.clone()))
+            .expression_attribute_values(
+                ":start_time",
+                AttributeValue::S(lp_sk(query.queue, query.start_time)),
+            )
+            .expression_attribute_values(
+                ":end_time",
+                AttributeValue::S(lp_sk(query.queue, query.end_time)),
+            )
+            .scan_index_forward(query.scan_forward)
+            .set_limit(query.limit.map(|limit| limit as i32))
+            .set_exclusive_start_key(start.map(start_key))
+            .send()
+            .await?;
+
+        page(res.items, res.last_evaluated_key)
+    }
+    async fn query_items(
+        &self,
+        query: &ItemQuery,
+        start: Option<PageToken>,
+    ) -> Result<Page<Value>> {
+        let res = self
+            .inner
+            .query()
+            .table_name(self.table_name.as_str())
+            .key_condition_expression("id = :id AND sk BETWEEN :start_sk AND :end_sk")
+            .expression_attribute_values(":id", AttributeValue::S(query.id.clone()))
+            .expression_attribute_values(":start_sk", AttributeValue::S(query.start_sk.clone()))
+            .expression_attribute_values(":end_sk", AttributeValue::S(query.end_sk.clone()))
+            .scan_index_forward(query.scan_forward)
+            .set_limit(query.limit.map(|limit| limit as i32))
+            .set_exclusive_start_key(start.map(start_key))
+            .send()
+            .await?;
+
+        page(res.items, res.last_evaluated_key)
+    }
+    async fn get_day(&self, query: &DayQuery, start: Option<PageToken>) -> Result<Page<LpItem>> {
+        let res = self
+            .inner
+            .query()
+            .table_name(self.table_name.as_str())
+            .index_name(DAY_INDEX)
+            .key_condition_expression("#day = :day AND sk BETWEEN :start_time AND :end_time")
+            // `day` is a reserved word
+            .expression_attribute_names("#day", "day")
+            .expression_attribute_values(":day", AttributeValue::S(query.day.clone()))
+            .expression_attribute_values(
+                ":start_time",
+                AttributeValue::S(lp_sk(query.queue, query.start_time)),
+            )
+            .expression_attribute_values(
+                ":end_time",
+                AttributeValue::S(lp_sk(query.queue, query.end_time)),
+            )
+            .set_limit(query.limit.map(|limit| limit as i32))
+            .set_exclusive_start_key(start.map(|start| {
+                let mut key = start_key(start);
+                key.insert("day".to_string(), AttributeValue::S(query.day.clone()));
+                key
+            }))
+            .send()
+            .await?;
+
+        page(res.items, res.last_evaluated_key)
+    }
+}
+
+/// Whether a transaction was cancelled by one of its condition expressions
+fn cancelled_by_condition<R>(err: &SdkError<TransactWriteItemsError, R>) -> bool {
+    let SdkError::ServiceError(context) = err else {
+        return false;
+    };
+    let TransactWriteItemsError::TransactionCanceledException(cancelled) = context.err() else {
+        return false;
+    };
+    cancelled
+        .cancellation_reasons()
+        .unwrap_or_default()
+        .iter()
+        .any(|reason| reason.code() == Some("ConditionalCheckFailed"))
+}
+
+/// Wait before retrying the unprocessed part of a batch request, with an exponential backoff
+async fn backoff(attempt: u32, unprocessed: usize) -> Result<()> {
+    match attempt {
+        0 => Ok(()),
+        attempt if attempt > BATCH_RETRIES => Err(Error::AwsSdk(format!(
+            "{} batch items left unprocessed after {} retries",
+            unprocessed, BATCH_RETRIES
+        ))),
+        attempt => {
+            tokio::time::sleep(Duration::from_millis(50 << attempt)).await;
+            Ok(())
+        }
+    }
+}
+
+fn key(id: String, sk: String) -> Item {
+    HashMap::from([
+        ("id".to_string(), AttributeValue::S(id)),
+        ("sk".to_string(), AttributeValue::S(sk)),
+    ])
+}
+
+fn start_key(token: PageToken) -> Item {
+    HashMap::from([
+        ("id".to_string(), AttributeValue::S(token.id)),
+        ("sk".to_string(), AttributeValue::S(token.sk)),
+    ])
+}
+
+fn page<T: serde::de::DeserializeOwned>(
+    items: Option<Vec<HashMap<String, AttributeValue>>>,
+    last_evaluated_key: Option<HashMap<String, AttributeValue>>,
+) -> Result<Page<T>> {
+    let next = last_evaluated_key.and_then(|key| {
+        Some(PageToken {
+            id: key.get("id")?.as_s().ok()?.clone(),
+            sk: key.get("sk")?.as_s().ok()?.clone(),
+        })
+    });
+    Ok(Page {
+        items: from_items(items.unwrap_or_default())?,
+        next,
+    })
+}
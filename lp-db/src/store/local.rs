@@ -0,0 +1,326 @@
+//! Shared logic of the backends storing serialized items in a local sorted table
+use super::{BetweenQuery, DayQuery, ItemQuery, LpStore, Page, PageToken};
+use crate::builders::league_points::{lp_sk, LatestStanding, LpItem};
+use crate::builders::tracking::{tracked_by_prefix, tracking_partition, tracking_sk, TrackingItem};
+use crate::error::{Error, Result};
+use crate::{ident, GameType, ItemModel, Queue};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::fmt::Debug;
+
+/// Sort key condition of a query, mirroring the DynamoDB key conditions
+pub(crate) enum SkCondition<'a> {
+    BeginsWith(&'a str),
+    /// Inclusive on both ends
+    Between(&'a str, &'a str),
+}
+
+impl SkCondition<'_> {
+    pub(crate) fn matches(&self, sk: &str) -> bool {
+        match self {
+            SkCondition::BeginsWith(prefix) => sk.starts_with(prefix),
+            SkCondition::Between(start, end) => *start <= sk && sk <= *end,
+        }
+    }
+}
+
+/// A query on the items of one partition
+pub(crate) struct Query<'a> {
+    pub(crate) id: &'a str,
+    pub(crate) sk: SkCondition<'a>,
+    /// Only return items sorted after this sort key (before it when scanning backward)
+    pub(crate) start_after: Option<&'a str>,
+    pub(crate) scan_forward: bool,
+    pub(crate) limit: Option<usize>,
+}
+
+impl Query<'_> {
+    pub(crate) fn after_start(&self, sk: &str) -> bool {
+        match self.start_after {
+            Some(start) if self.scan_forward => sk > start,
+            Some(start) => sk < start,
+            None => true,
+        }
+    }
+}
+
+/// A table of JSON items keyed by `(id, sk)`
+pub(crate) trait Table: Debug + Send + Sync {
+    fn put(&self, id: &str, sk: &str, value: String) -> Result<()>;
+    /// Insert an item unless its key exists, returns whether it was inserted
+    fn insert(&self, id: &str, sk: &str, value: String) -> Result<bool>;
+    /// Insert or replace an item unless `keep` holds for its current value, `None` when the item
+    /// is missing, returns whether it was written
+    fn put_unless(
+        &self,
+        id: &str,
+        sk: &str,
+        value: String,
+        keep: &(dyn Fn(Option<&str>) -> bool + Sync),
+    ) -> Result<bool>;
+    /// Returns whether the item existed
+    fn delete(&self, id: &str, sk: &str) -> Result<bool>;
+    fn get(&self, id: &str, sk: &str) -> Result<Option<String>>;
+    /// Get the `(sk, value)` pairs matching `query`, in the query order
+    fn query(&self, query: &Query<'_>) -> Result<Vec<(String, String)>>;
+    /// Get the `(id, sk, value)` triples of the items of the `byDay` index partition `day` with a
+    /// sort key between `start` and `end` (inclusive), ordered by sort key then partition key
+    fn query_day(&self, day: &str, start: &str, end: &str)
+        -> Result<Vec<(String, String, String)>>;
+}
+
+/// Items that no longer match their model are validation errors, as with serde_dynamo
+fn decode<T: serde::de::DeserializeOwned>(value: &str) -> Result<T> {
+    serde_json::from_str(value).map_err(|e| Error::Validation(e.to_string()))
+}
+
+fn page<T: serde::de::DeserializeOwned>(
+    id: &str,
+    limit: Option<usize>,
+    rows: Vec<(String, String)>,
+) -> Result<Page<T>> {
+    // Like DynamoDB, a full page always points to a next one, which may be empty
+    let next = match (limit, rows.last()) {
+        (Some(limit), Some((sk, _))) if rows.len() >= limit => Some(PageToken {
+            id: id.to_string(),
+            sk: sk.clone(),
+        }),
+        _ => None,
+    };
+    let items = rows
+        .iter()
+        .map(|(_, value)| decode(value))
+        .collect::<Result<_>>()?;
+    Ok(Page { items, next })
+}
+
+#[async_trait]
+impl<T: Table> LpStore for T {
+    async fn track(&self, item: TrackingItem) -> Result<()> {
+        let shared = item.shared();
+        self.put(&item.item.id, &item.item.sk, serde_json::to_string(&item)?)?;
+        self.put(
+            &shared.item.id,
+            &shared.item.sk,
+            serde_json::to_string(&shared)?,
+        )
+    }
+    async fn track_new(&self, item: TrackingItem, audit: Option<Value>) -> Result<()> {
+        let shared = item.shared();
+        if !self.insert(&item.item.id, &item.item.sk, serde_json::to_string(&item)?)? {
+            return Err(Error::AlreadyTracked);
+        }
+        self.put(
+            &shared.item.id,
+            &shared.item.sk,
+            serde_json::to_string(&shared)?,
+        )?;
+        self.put_items(audit.into_iter().collect()).await
+    }
+    async fn untrack(
+        &self,
+        game_type: GameType,
+        guild_id: Option<&str>,
+        puuid: &str,
+        audit: Option<Value>,
+    ) -> Result<()> {
+        let tracked = self.delete(
+            &tracking_partition(guild_id),
+            &tracking_sk(game_type, puuid, None),
+        )?;
+        if !tracked {
+            return Err(Error::NotTracked);
+        }
+        if guild_id.is_some() {
+            self.delete(
+                &tracking_partition(None),
+                &tracking_sk(game_type, puuid, guild_id),
+            )?;
+        }
+        self.put_items(audit.into_iter().collect()).await
+    }
+    async fn list_tracked(
+        &self,
+        game_type: GameType,
+        guild_id: Option<&str>,
+        start: Option<PageToken>,
+    ) -> Result<Page<TrackingItem>> {
+        let id = tracking_partition(guild_id);
+        let prefix = format!("SUMMONER#{}", ident(game_type));
+        let rows = self.query(&Query {
+            id: &id,
+            sk: SkCondition::BeginsWith(&prefix),
+            start_after: start.as_ref().map(|start| start.sk.as_str()),
+            scan_forward: true,
+            limit: None,
+        })?;
+        page(&id, None, rows)
+    }
+    async fn list_tracked_by(
+        &self,
+        game_type: GameType,
+        puuid: &str,
+        start: Option<PageToken>,
+    ) -> Result<Page<TrackingItem>> {
+        let id = tracking_partition(None);
+        let prefix = tracked_by_prefix(game_type, puuid);
+        let rows = self.query(&Query {
+            id: &id,
+            sk: SkCondition::BeginsWith(&prefix),
+            start_after: start.as_ref().map(|start| start.sk.as_str()),
+            scan_forward: true,
+            limit: None,
+        })?;
+        page(&id, None, rows)
+    }
+    async fn add(&self, item: LpItem) -> Result<()> {
+        self.put(&item.item.id, &item.item.sk, serde_json::to_string(&item)?)
+    }
+    async fn add_unless_unchanged(&self, item: LpItem, since: i64) -> Result<bool> {
+        let standing = LatestStanding::of(&item)
+            .ok_or_else(|| Error::Validation(format!("{} is not a snapshot", item.item.sk)))?;
+        let keep = |current: Option<&str>| {
+            current
+                .and_then(|current| decode::<LatestStanding>(current).ok())
+                .is_some_and(|current| current.unchanged(&standing, since))
+        };
+        let written = self.put_unless(
+            &standing.item.id,
+            &standing.item.sk,
+            serde_json::to_string(&standing)?,
+            &keep,
+        )?;
+        if written {
+            self.put(&item.item.id, &item.item.sk, serde_json::to_string(&item)?)?;
+        }
+        Ok(written)
+    }
+    async fn add_many(&self, items: Vec<LpItem>) -> Result<()> {
+        for item in items {
+            self.put(&item.item.id, &item.item.sk, serde_json::to_string(&item)?)?;
+        }
+        Ok(())
+    }
+    async fn get_many(
+        &self,
+        queue: Queue,
+        puuids: &[String],
+        timestamp: i64,
+    ) -> Result<Vec<LpItem>> {
+        let sk = lp_sk(queue, timestamp);
+        let mut items = Vec::new();
+        for puuid in puuids {
+            if let Some(value) = Table::get(self, puuid, &sk)? {
+                items.push(decode(&value)?);
+            }
+        }
+        Ok(items)
+    }
+    async fn put_items(&self, items: Vec<Value>) -> Result<()> {
+        for item in items {
+            let key = |field| {
+                item.get(field)
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| Error::missing(field))
+            };
+            self.put(key("id")?, key("sk")?, item.to_string())?;
+        }
+        Ok(())
+    }
+    async fn put_versioned(&self, item: Value, previous: Option<u64>) -> Result<bool> {
+        let key = |field| {
+            item.get(field)
+                .and_then(Value::as_str)
+                .ok_or_else(|| Error::missing(field))
+        };
+        let keep = |current: Option<&str>| match (current, previous) {
+            (Some(current), Some(previous)) => decode::<Value>(current)
+                .map(|current| current.get("version").and_then(Value::as_u64).unwrap_or(0))
+                .map_or(true, |version| version != previous),
+            (None, None) => false,
+            _ => true,
+        };
+        self.put_unless(key("id")?, key("sk")?, item.to_string(), &keep)
+    }
+    async fn get_items(&self, keys: &[ItemModel]) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        for key in keys {
+            if let Some(value) = Table::get(self, &key.id, &key.sk)? {
+                items.push(decode(&value)?);
+            }
+        }
+        Ok(items)
+    }
+    async fn remove_items(&self, keys: &[ItemModel]) -> Result<()> {
+        for key in keys {
+            self.delete(&key.id, &key.sk)?;
+        }
+        Ok(())
+    }
+    async fn query_items(
+        &self,
+        query: &ItemQuery,
+        start: Option<PageToken>,
+    ) -> Result<Page<Value>> {
+        let rows = self.query(&Query {
+            id: &query.id,
+            sk: SkCondition::Between(&query.start_sk, &query.end_sk),
+            start_after: start.as_ref().map(|start| start.sk.as_str()),
+            scan_forward: query.scan_forward,
+            limit: query.limit,
+        })?;
+        page(&query.id, query.limit, rows)
+    }
+    async fn remove(&self, queue: Queue, puuid: &str, timestamp: i64) -> Result<()> {
+        self.delete(puuid, &lp_sk(queue, timestamp))?;
+        Ok(())
+    }
+    async fn get(&self, queue: Queue, puuid: &str, timestamp: i64) -> Result<Option<LpItem>> {
+        match Table::get(self, puuid, &lp_sk(queue, timestamp))? {
+            Some(value) => Ok(Some(decode(&value)?)),
+            None => Ok(None),
+        }
+    }
+    async fn get_between(
+        &self,
+        query: &BetweenQuery,
+        start: Option<PageToken>,
+    ) -> Result<Page<LpItem>> {
+        let start_sk = lp_sk(query.queue, query.start_time);
+        let end_sk = lp_sk(query.queue, query.end_time);
+        let rows = self.query(&Query {
+            id: &query.puuid,
+            sk: SkCondition::Between(&start_sk, &end_sk),
+            start_after: start.as_ref().map(|start| start.sk.as_str()),
+            scan_forward: query.scan_forward,
+            limit: query.limit,
+        })?;
+        page(&query.puuid, query.limit, rows)
+    }
+    async fn get_day(&self, query: &DayQuery, start: Option<PageToken>) -> Result<Page<LpItem>> {
+        let start_sk = lp_sk(query.queue, query.start_time);
+        let end_sk = lp_sk(query.queue, query.end_time);
+        // Sort keys are shared by the players, the partition key breaks the ties
+        let rows: Vec<_> = self
+            .query_day(&query.day, &start_sk, &end_sk)?
+            .into_iter()
+            .filter(|(id, sk, _)| match &start {
+                Some(start) => (sk, id) > (&start.sk, &start.id),
+                None => true,
+            })
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect();
+        let next = match (query.limit, rows.last()) {
+            (Some(limit), Some((id, sk, _))) if rows.len() >= limit => Some(PageToken {
+                id: id.clone(),
+                sk: sk.clone(),
+            }),
+            _ => None,
+        };
+        let items = rows
+            .iter()
+            .map(|(_, _, value)| decode(value))
+            .collect::<Result<_>>()?;
+        Ok(Page { items, next })
+    }
+}
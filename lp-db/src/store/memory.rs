@@ -0,0 +1,169 @@
+use super::local::{Query, Table};
+use crate::error::Result;
+use serde_json::Value;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// [`super::LpStore`] keeping every item in memory, meant for tests and local runs
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    items: Mutex<BTreeMap<(String, String), String>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Table for MemoryStore {
+    fn put(&self, id: &str, sk: &str, value: String) -> Result<()> {
+        self.items
+            .lock()
+            .expect("memory store lock poisoned")
+            .insert((id.to_string(), sk.to_string()), value);
+        Ok(())
+    }
+    fn insert(&self, id: &str, sk: &str, value: String) -> Result<bool> {
+        let mut items = self.items.lock().expect("memory store lock poisoned");
+        match items.entry((id.to_string(), sk.to_string())) {
+            Entry::Occupied(_) => Ok(false),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                Ok(true)
+            }
+        }
+    }
+    fn put_unless(
+        &self,
+        id: &str,
+        sk: &str,
+        value: String,
+        keep: &(dyn Fn(Option<&str>) -> bool + Sync),
+    ) -> Result<bool> {
+        let mut items = self.items.lock().expect("memory store lock poisoned");
+        let key = (id.to_string(), sk.to_string());
+        if keep(items.get(&key).map(String::as_str)) {
+            return Ok(false);
+        }
+        items.insert(key, value);
+        Ok(true)
+    }
+    fn delete(&self, id: &str, sk: &str) -> Result<bool> {
+        let removed = self
+            .items
+            .lock()
+            .expect("memory store lock poisoned")
+            .remove(&(id.to_string(), sk.to_string()));
+        Ok(removed.is_some())
+    }
+    fn get(&self, id: &str, sk: &str) -> Result<Option<String>> {
+        let items = self.items.lock().expect("memory store lock poisoned");
+        Ok(items.get(&(id.to_string(), sk.to_string())).cloned())
+    }
+    fn query(&self, query: &Query<'_>) -> Result<Vec<(String, String)>> {
+        let items = self.items.lock().expect("memory store lock poisoned");
+        // Every key of the partition sorts between these two bounds
+        let partition =
+            (query.id.to_string(), String::new())..(format!("{}\0", query.id), String::new());
+        let rows = items
+            .range(partition)
+            .filter(|((_, sk), _)| query.sk.matches(sk) && query.after_start(sk))
+            .map(|((_, sk), value)| (sk.clone(), value.clone()));
+        let rows: Vec<_> = if query.scan_forward {
+            rows.collect()
+        } else {
+            rows.rev().collect()
+        };
+        Ok(rows
+            .into_iter()
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+    fn query_day(
+        &self,
+        day: &str,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<(String, String, String)>> {
+        let items = self.items.lock().expect("memory store lock poisoned");
+        // No index to keep up to date, every item is looked at
+        let mut rows: Vec<_> = items
+            .iter()
+            .filter(|((_, sk), _)| start <= sk.as_str() && sk.as_str() <= end)
+            .filter(|(_, value)| {
+                serde_json::from_str::<Value>(value)
+                    .is_ok_and(|value| value.get("day").and_then(Value::as_str) == Some(day))
+            })
+            .map(|((id, sk), value)| (id.clone(), sk.clone(), value.clone()))
+            .collect();
+        rows.sort_by(|(id_a, sk_a, _), (id_b, sk_b, _)| (sk_a, id_a).cmp(&(sk_b, id_b)));
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, GameType};
+
+    #[tokio::test]
+    async fn track_and_snapshots() {
+        let client = Client::in_memory();
+        let tracking = client.tracking(GameType::League);
+        tracking
+            .track_user()
+            .guild_id("guild")
+            .puuid("puuid")
+            .account_id("account")
+            .summoner_name("monke")
+            .send()
+            .await
+            .unwrap();
+        assert!(client
+            .tracking(GameType::Tft)
+            .list()
+            .send()
+            .await
+            .unwrap()
+            .is_empty());
+        let tracked = tracking.list().send().await.unwrap();
+        assert_eq!(tracked[0].puuid, "puuid");
+
+        for timestamp in [100, 200, 300] {
+            client
+                .league_points(GameType::League)
+                .add()
+                .puuid("summoner")
+                .timestamp(timestamp)
+                .tier("GOLD")
+                .rank("II")
+                .league_points(50)
+                .wins(10)
+                .losses(5)
+                .send()
+                .await
+                .unwrap();
+        }
+        let snapshots = client
+            .league_points(GameType::League)
+            .get_between()
+            .puuid("summoner")
+            .start_time(150)
+            .end_time(300)
+            .send()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshots.len(), 2);
+
+        tracking
+            .untrack_user()
+            .guild_id("guild")
+            .puuid("puuid")
+            .send()
+            .await
+            .unwrap();
+        assert!(tracking.list().send().await.unwrap().is_empty());
+    }
+}
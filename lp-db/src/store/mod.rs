@@ -0,0 +1,175 @@
+//! Storage backends behind [`crate::Client`]
+//!
+//! Every backend keeps the single table layout of the DynamoDB table: items are addressed by a
+//! partition key (`id`) and a sort key (`sk`).
+use crate::builders::league_points::LpItem;
+use crate::builders::tracking::TrackingItem;
+use crate::error::{Error, Result};
+use crate::{GameType, ItemModel, Queue};
+use async_trait::async_trait;
+use futures::stream::{self, Stream, TryStreamExt};
+use serde_json::Value;
+use std::fmt::Debug;
+use std::future::Future;
+
+pub mod dynamo;
+mod local;
+pub mod memory;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+pub use dynamo::DynamoStore;
+pub use memory::MemoryStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+/// Operations behind the tracking and league points clients
+#[async_trait]
+pub trait LpStore: Debug + Send + Sync {
+    /// Insert or replace a summoner tracked by a guild, along with its copy in the shared list
+    async fn track(&self, item: TrackingItem) -> Result<()>;
+    /// Insert a summoner tracked by a guild along with its copy in the shared list, failing with
+    /// [`Error::AlreadyTracked`] without writing anything when the guild already tracks it
+    ///
+    /// `audit` is an item given as a JSON object, written along with the tracking items.
+    async fn track_new(&self, item: TrackingItem, audit: Option<Value>) -> Result<()>;
+    /// Stop tracking a summoner in a guild, without a guild only the item stored before tracking
+    /// was scoped by guild is removed
+    ///
+    /// Fails with [`Error::NotTracked`] without removing anything when the summoner is not
+    /// tracked. `audit` is an item given as a JSON object, written along with the removal.
+    async fn untrack(
+        &self,
+        game_type: GameType,
+        guild_id: Option<&str>,
+        puuid: &str,
+        audit: Option<Value>,
+    ) -> Result<()>;
+    /// List the summoners tracked by a guild, or the shared list when `guild_id` is `None`
+    async fn list_tracked(
+        &self,
+        game_type: GameType,
+        guild_id: Option<&str>,
+        start: Option<PageToken>,
+    ) -> Result<Page<TrackingItem>>;
+    /// List the copies in the shared list of a summoner tracked by guilds, one per guild
+    async fn list_tracked_by(
+        &self,
+        game_type: GameType,
+        puuid: &str,
+        start: Option<PageToken>,
+    ) -> Result<Page<TrackingItem>>;
+    /// Insert or replace a league points snapshot
+    async fn add(&self, item: LpItem) -> Result<()>;
+    /// Insert or replace a league points snapshot unless the player's latest standing in the
+    /// queue holds the same tier, rank, league points, wins and losses and was taken since
+    /// `since`, returns whether the snapshot was written
+    ///
+    /// The standing is replaced by the snapshot's in the same conditional write, see
+    /// [`crate::builders::league_points::add::AddBuilder::skip_unchanged_since`].
+    async fn add_unless_unchanged(&self, item: LpItem, since: i64) -> Result<bool>;
+    /// Insert or replace many league points snapshots
+    async fn add_many(&self, items: Vec<LpItem>) -> Result<()>;
+    /// Get the snapshots of many players taken at `timestamp`, players without one are skipped
+    async fn get_many(
+        &self,
+        queue: Queue,
+        puuids: &[String],
+        timestamp: i64,
+    ) -> Result<Vec<LpItem>>;
+    /// Insert or replace items given as JSON objects with their `id` and `sk`
+    async fn put_items(&self, items: Vec<Value>) -> Result<()>;
+    /// Insert or replace an item given as a JSON object with its `id`, `sk` and `version`, only
+    /// when the stored item is at version `previous`, or missing when `previous` is `None`
+    ///
+    /// Items without a `version` are at version 0. Returns whether the item was written.
+    async fn put_versioned(&self, item: Value, previous: Option<u64>) -> Result<bool>;
+    /// Get the items with these keys as JSON objects, in any order, missing items are skipped
+    async fn get_items(&self, keys: &[ItemModel]) -> Result<Vec<Value>>;
+    /// Remove the items with these keys, missing items are ignored
+    async fn remove_items(&self, keys: &[ItemModel]) -> Result<()>;
+    /// Get the items of a partition with a sort key in a range, as JSON objects
+    async fn query_items(&self, query: &ItemQuery, start: Option<PageToken>)
+        -> Result<Page<Value>>;
+    async fn remove(&self, queue: Queue, puuid: &str, timestamp: i64) -> Result<()>;
+    async fn get(&self, queue: Queue, puuid: &str, timestamp: i64) -> Result<Option<LpItem>>;
+    /// Get the snapshots taken between two timestamps (inclusive)
+    async fn get_between(
+        &self,
+        query: &BetweenQuery,
+        start: Option<PageToken>,
+    ) -> Result<Page<LpItem>>;
+    /// Get the snapshots of every player taken between two timestamps (inclusive) of a UTC day,
+    /// through the `byDay` index, ordered by timestamp
+    async fn get_day(&self, query: &DayQuery, start: Option<PageToken>) -> Result<Page<LpItem>>;
+}
+
+/// One page of query results
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Where the next page starts, `None` on the last page
+    pub next: Option<PageToken>,
+}
+
+/// Key of the last item of a page, the next page starts right after it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageToken {
+    pub id: String,
+    pub sk: String,
+}
+
+/// Snapshots of a player in a queue taken between two timestamps
+#[derive(Debug, Clone)]
+pub struct BetweenQuery {
+    pub queue: Queue,
+    pub puuid: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    /// Most items returned per page
+    pub limit: Option<usize>,
+    /// Oldest snapshots first when `true`
+    pub scan_forward: bool,
+}
+
+/// Items of a partition with a sort key between two sort keys (inclusive)
+#[derive(Debug, Clone)]
+pub struct ItemQuery {
+    pub id: String,
+    pub start_sk: String,
+    pub end_sk: String,
+    /// Most items returned per page
+    pub limit: Option<usize>,
+    /// Smallest sort keys first when `true`
+    pub scan_forward: bool,
+}
+
+/// Snapshots of every player in a queue taken between two timestamps of the same UTC day
+#[derive(Debug, Clone)]
+pub struct DayQuery {
+    pub queue: Queue,
+    /// See [`crate::builders::league_points::LpItem::day`]
+    pub day: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    /// Most items returned per page
+    pub limit: Option<usize>,
+}
+
+/// Stream every item of a paginated query, fetching pages as the stream is polled
+pub(crate) fn paginate<T, F, Fut>(fetch: F) -> impl Stream<Item = Result<T>>
+where
+    F: FnMut(Option<PageToken>) -> Fut,
+    Fut: Future<Output = Result<Page<T>>>,
+{
+    // `None` once the last page was fetched, the first page has no start token
+    stream::try_unfold((fetch, Some(None)), |(mut fetch, start)| async move {
+        let Some(start) = start else {
+            return Ok::<_, Error>(None);
+        };
+        let page = fetch(start).await?;
+        Ok(Some((page.items, (fetch, page.next.map(Some)))))
+    })
+    .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+    .try_flatten()
+}
@@ -0,0 +1,261 @@
+use super::local::{Query, SkCondition, Table};
+use crate::error::Result;
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// [`super::LpStore`] backed by a SQLite database, for self-hosting on a single box
+///
+/// Queries run on the calling task, which is fine for the handful of rows the service reads at
+/// a time.
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Open (or create) the database at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+    /// Open a database living only as long as the store
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+    fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS items (
+                id TEXT NOT NULL,
+                sk TEXT NOT NULL,
+                data TEXT NOT NULL,
+                PRIMARY KEY (id, sk)
+            ) WITHOUT ROWID;
+            -- Mirrors the byDay index of the DynamoDB table
+            CREATE INDEX IF NOT EXISTS items_by_day ON items (json_extract(data, '$.day'), sk)
+                WHERE json_extract(data, '$.day') IS NOT NULL;",
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+impl Table for SqliteStore {
+    fn put(&self, id: &str, sk: &str, value: String) -> Result<()> {
+        let conn = self.conn.lock().expect("sqlite store lock poisoned");
+        conn.execute(
+            "INSERT OR REPLACE INTO items (id, sk, data) VALUES (?1, ?2, ?3)",
+            params![id, sk, value],
+        )?;
+        Ok(())
+    }
+    fn insert(&self, id: &str, sk: &str, value: String) -> Result<bool> {
+        let conn = self.conn.lock().expect("sqlite store lock poisoned");
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO items (id, sk, data) VALUES (?1, ?2, ?3)",
+            params![id, sk, value],
+        )?;
+        Ok(inserted > 0)
+    }
+    fn put_unless(
+        &self,
+        id: &str,
+        sk: &str,
+        value: String,
+        keep: &(dyn Fn(Option<&str>) -> bool + Sync),
+    ) -> Result<bool> {
+        // The connection stays locked between the read and the write
+        let conn = self.conn.lock().expect("sqlite store lock poisoned");
+        let current: Option<String> = conn
+            .query_row(
+                "SELECT data FROM items WHERE id = ?1 AND sk = ?2",
+                params![id, sk],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if keep(current.as_deref()) {
+            return Ok(false);
+        }
+        conn.execute(
+            "INSERT OR REPLACE INTO items (id, sk, data) VALUES (?1, ?2, ?3)",
+            params![id, sk, value],
+        )?;
+        Ok(true)
+    }
+    fn delete(&self, id: &str, sk: &str) -> Result<bool> {
+        let conn = self.conn.lock().expect("sqlite store lock poisoned");
+        let deleted = conn.execute(
+            "DELETE FROM items WHERE id = ?1 AND sk = ?2",
+            params![id, sk],
+        )?;
+        Ok(deleted > 0)
+    }
+    fn get(&self, id: &str, sk: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock().expect("sqlite store lock poisoned");
+        let value = conn
+            .query_row(
+                "SELECT data FROM items WHERE id = ?1 AND sk = ?2",
+                params![id, sk],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value)
+    }
+    fn query(&self, query: &Query<'_>) -> Result<Vec<(String, String)>> {
+        let conn = self.conn.lock().expect("sqlite store lock poisoned");
+        let mut sql = "SELECT sk, data FROM items WHERE id = ?".to_string();
+        let mut values: Vec<&dyn ToSql> = vec![&query.id];
+        match &query.sk {
+            SkCondition::BeginsWith(prefix) => {
+                sql.push_str(" AND substr(sk, 1, length(?)) = ?");
+                values.extend([prefix as &dyn ToSql, prefix]);
+            }
+            SkCondition::Between(start, end) => {
+                sql.push_str(" AND sk BETWEEN ? AND ?");
+                values.extend([start as &dyn ToSql, end]);
+            }
+        }
+        if let Some(start_after) = &query.start_after {
+            sql.push_str(if query.scan_forward {
+                " AND sk > ?"
+            } else {
+                " AND sk < ?"
+            });
+            values.push(start_after);
+        }
+        sql.push_str(if query.scan_forward {
+            " ORDER BY sk ASC"
+        } else {
+            " ORDER BY sk DESC"
+        });
+        // SQLite treats a negative limit as no limit
+        let limit = query.limit.map_or(-1, |limit| limit as i64);
+        sql.push_str(" LIMIT ?");
+        values.push(&limit);
+
+        let rows = conn
+            .prepare_cached(&sql)?
+            .query_map(values.as_slice(), |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+    fn query_day(
+        &self,
+        day: &str,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<(String, String, String)>> {
+        let conn = self.conn.lock().expect("sqlite store lock poisoned");
+        let rows = conn
+            .prepare_cached(
+                "SELECT id, sk, data FROM items
+                WHERE json_extract(data, '$.day') = ?1 AND sk BETWEEN ?2 AND ?3
+                ORDER BY sk ASC, id ASC",
+            )?
+            .query_map(params![day, start, end], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SqliteStore;
+    use crate::builders::league_points::{day_key, LpItem};
+    use crate::store::{BetweenQuery, DayQuery, LpStore};
+    use crate::{Client, GameType, Queue};
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[tokio::test]
+    async fn get_snapshot() {
+        let client = Client::sqlite(":memory:").unwrap();
+        let league_points = client.league_points(GameType::Tft);
+        league_points
+            .add()
+            .puuid("summoner")
+            .timestamp(100)
+            .tier("DIAMOND")
+            .rank("IV")
+            .league_points(12)
+            .wins(30)
+            .losses(28)
+            .send()
+            .await
+            .unwrap();
+
+        let snapshot = league_points
+            .get()
+            .puuid("summoner")
+            .timestamp(100)
+            .send()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.league_points, 12);
+        assert!(client
+            .league_points(GameType::League)
+            .get()
+            .puuid("summoner")
+            .timestamp(100)
+            .send()
+            .await
+            .unwrap()
+            .is_none());
+    }
+    #[tokio::test]
+    async fn paginated_between() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        for timestamp in 1..=5 {
+            let item = LpItem::new(Queue::Solo, "summoner", timestamp, "GOLD", "I", 0, 0, 0);
+            store.add(item).await.unwrap();
+        }
+        let query = BetweenQuery {
+            queue: Queue::Solo,
+            puuid: "summoner".to_string(),
+            start_time: 2,
+            end_time: 5,
+            limit: Some(3),
+            scan_forward: true,
+        };
+        let first = store.get_between(&query, None).await.unwrap();
+        assert_eq!(first.items.len(), 3);
+        let second = store.get_between(&query, first.next).await.unwrap();
+        assert_eq!(second.items.len(), 1);
+        assert!(second.next.is_none());
+    }
+    #[tokio::test]
+    async fn paginated_day() {
+        const T: i64 = 1704085200;
+        let store = SqliteStore::open_in_memory().unwrap();
+        // Players share the sort keys of the snapshots taken at the same time
+        for (puuid, timestamp) in [("a", T), ("b", T), ("c", T), ("a", T + 60), ("a", T - DAY)] {
+            let item = LpItem::new(Queue::Solo, puuid, timestamp, "GOLD", "I", 0, 0, 0);
+            store.add(item).await.unwrap();
+        }
+        let query = DayQuery {
+            queue: Queue::Solo,
+            day: day_key(GameType::League, T).unwrap(),
+            start_time: T,
+            end_time: T + 3600,
+            limit: Some(2),
+        };
+        let mut snapshots = Vec::new();
+        let mut start = None;
+        loop {
+            let page = store.get_day(&query, start).await.unwrap();
+            snapshots.extend(page.items);
+            match page.next {
+                Some(next) => start = Some(next),
+                None => break,
+            }
+        }
+        let keys = snapshots
+            .iter()
+            .map(|snapshot| (snapshot.puuid(), snapshot.timestamp().timestamp()))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, [("a", T), ("b", T), ("c", T), ("a", T + 60)]);
+    }
+}
@@ -1,11 +1,95 @@
-use chrono::{TimeZone, Utc};
+use async_trait::async_trait;
+use chrono::{NaiveTime, TimeZone, Utc};
 use chrono_tz::US::Eastern;
 use lambda_runtime::LambdaEvent;
-use lp_db::GameType;
+use lp_db::builders::league_points::compact::DEFAULT_WINDOW;
+use lp_db::builders::league_points::retention::RetentionPolicy;
+use lp_db::builders::league_points::LpItem;
+use lp_db::builders::tracking::migrate::PuuidResolver;
+use lp_db::builders::tracking::TrackingItem;
+use lp_db::season::SeasonCalendar;
+use lp_db::{GameType, Queue};
+use riot_sdk::account::AccountRegion;
 use riot_sdk::summoner;
 use serde_json::Value;
 use tokio::time::{sleep, Duration};
 
+/// Resolves the PUUIDs of the TFT players with the TFT API key
+///
+/// TFT players used to be tracked with the PUUID of the League API key, the Riot ID of the
+/// player is the same for both keys.
+struct TftPuuids {
+    league: riot_sdk::AccountClient,
+    tft: riot_sdk::AccountClient,
+}
+
+#[async_trait]
+impl PuuidResolver for TftPuuids {
+    async fn resolve(&self, item: &TrackingItem) -> lp_db::error::Result<String> {
+        // Players tracked since TFT PUUIDs are resolved with the TFT key
+        let tft_account = self
+            .tft
+            .account(AccountRegion::AMERICAS)
+            .get_by_puuid(&item.puuid)
+            .send()
+            .await;
+        if tft_account.is_ok() {
+            return Ok(item.puuid.clone());
+        }
+        let resolve = async {
+            let account = self
+                .league
+                .account(AccountRegion::AMERICAS)
+                .get_by_puuid(&item.puuid)
+                .send()
+                .await?;
+            self.tft
+                .account(AccountRegion::AMERICAS)
+                .get_by_riot_id(&account.game_name, &account.tag_line)
+                .send()
+                .await
+        };
+        match resolve.await {
+            Ok(account) => Ok(account.puuid),
+            Err(e) => Err(lp_db::error::Error::Validation(format!(
+                "Unable to resolve the TFT PUUID of {}: {}",
+                item.summoner_name, e
+            ))),
+        }
+    }
+}
+
+/// Record the snapshots as the peak ranks of the season in the profiles of their players
+///
+/// Peaks are kept per season, nothing is recorded outside of the splits of the season calendar.
+/// Players without a profile are skipped, profiles are created when players are tracked.
+async fn record_peaks(
+    client: &lp_db::Client,
+    game_type: GameType,
+    timestamp: i64,
+    snapshots: Vec<LpItem>,
+) -> lp_db::error::Result<()> {
+    let Some(split) = client.seasons().split_at(game_type, timestamp) else {
+        return Ok(());
+    };
+    let puuids: Vec<String> = snapshots.iter().map(|s| s.puuid().to_string()).collect();
+    let profiles = client.profiles().get_many().puuids(&puuids).send().await?;
+    for mut profile in profiles {
+        let puuid = profile.puuid().to_string();
+        let mut update = client.profiles().update().puuid(&puuid);
+        let mut changed = false;
+        for snapshot in snapshots.iter().filter(|s| s.puuid() == puuid) {
+            // Only the profiles whose peak rank changed are written
+            changed |= profile.record_peak(&split.season, snapshot);
+            update = update.peak(&split.season, snapshot.clone());
+        }
+        if changed {
+            update.send().await?;
+        }
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), lambda_runtime::Error> {
     tracing_subscriber::fmt()
@@ -20,7 +104,7 @@ async fn main() -> Result<(), lambda_runtime::Error> {
 
     Ok(())
 }
-async fn handler(_e: LambdaEvent<Value>) -> Result<(), lambda_runtime::Error> {
+async fn handler(e: LambdaEvent<Value>) -> Result<(), lambda_runtime::Error> {
     let now = Utc::now().with_timezone(&Eastern);
 
     let start_time = now
@@ -28,16 +112,163 @@ async fn handler(_e: LambdaEvent<Value>) -> Result<(), lambda_runtime::Error> {
         .from_local_datetime(&now.date_naive().and_time(now.time()))
         .unwrap()
         .timestamp();
+    // Retries of a run are skipped when nothing changed, the runs at 23:59 and 00:00 are on
+    // different days and always write their snapshots
+    let midnight = Eastern
+        .from_local_datetime(&now.date_naive().and_time(NaiveTime::MIN))
+        .earliest()
+        .map_or(start_time, |midnight| midnight.timestamp());
+    let unchanged_since = (start_time - DEFAULT_WINDOW).max(midnight);
     let config = aws_config::load_from_env().await;
     let league_client =
         riot_sdk::LeagueClient::new(&std::env::var("RIOT_API_KEY").expect("RIOT_API_KEY not set"));
     let tft_client = riot_sdk::TftClient::new(
         &std::env::var("TFT_RIOT_API_KEY").expect("TFT_RIOT_API_KEY not set"),
     );
-    let lp_db_client = lp_db::Client::new(
+    let mut lp_db_client = lp_db::Client::new(
         &std::env::var("LP_DB_TABLE_NAME").expect("LP_DB_TABLE_NAME not set"),
         &config,
-    );
+    )
+    .with_seasons(SeasonCalendar::from_env()?);
+    // Old snapshots are only downsampled when a retention policy is configured
+    let retention = RetentionPolicy::from_env()?;
+    if let Some(policy) = retention {
+        lp_db_client = lp_db_client.with_retention(policy)?;
+    }
+    // Invoked manually with `{"migrate_guild_id": "<guild id>"}` to move the summoners tracked
+    // before tracking was scoped by guild. Must run before `migrate_puuid`, which finds the
+    // history stored by summoner id through the tracking items of the guilds
+    if let Some(guild_id) = e.payload.get("migrate_guild_id").and_then(Value::as_str) {
+        for game_type in [GameType::League, GameType::Tft] {
+            let migrated = lp_db_client
+                .tracking(game_type)
+                .migrate_to_guild()
+                .guild_id(guild_id)
+                .send()
+                .await?;
+            tracing::info!(
+                "Migrated {} {:?} summoners to guild {}",
+                migrated,
+                game_type,
+                guild_id
+            );
+        }
+        return Ok(());
+    }
+    // Invoked manually with `{"migrate_puuid": true}` to re-key the summoners and league points
+    // stored by encrypted summoner id, after `migrate_guild_id`. TFT players are re-keyed by
+    // their PUUID for the TFT API key
+    if e.payload.get("migrate_puuid").and_then(Value::as_bool) == Some(true) {
+        for game_type in [GameType::League, GameType::Tft] {
+            let mut migration = lp_db_client.tracking(game_type).migrate_to_puuid();
+            if game_type == GameType::Tft {
+                migration = migration.resolver(TftPuuids {
+                    league: riot_sdk::AccountClient::new(
+                        &std::env::var("RIOT_API_KEY").expect("RIOT_API_KEY not set"),
+                    ),
+                    tft: riot_sdk::AccountClient::new(
+                        &std::env::var("TFT_RIOT_API_KEY").expect("TFT_RIOT_API_KEY not set"),
+                    ),
+                });
+            }
+            let migration = migration.send().await?;
+            tracing::info!(
+                "Migrated {} {:?} summoners and {} snapshots to PUUID",
+                migration.tracking,
+                game_type,
+                migration.snapshots
+            );
+        }
+        return Ok(());
+    }
+    // Invoked manually with `{"compact": true}` to remove the snapshots of retried or manual
+    // invocations
+    if e.payload.get("compact").and_then(Value::as_bool) == Some(true) {
+        for game_type in [GameType::League, GameType::Tft] {
+            let summoners = lp_db_client.tracking(game_type).list().send().await?;
+            let mut removed = 0;
+            for summoner in &summoners {
+                for queue in Queue::ALL
+                    .into_iter()
+                    .filter(|q| q.game_type() == game_type)
+                {
+                    removed += lp_db_client
+                        .league_points(game_type)
+                        .compact()
+                        .timezone(&Eastern)
+                        .puuid(&summoner.puuid)
+                        .queue(queue)
+                        .send()
+                        .await?;
+                }
+            }
+            tracing::info!("Removed {} redundant {:?} snapshots", removed, game_type);
+        }
+        return Ok(());
+    }
+    // Invoked weekly with `{"retention": true}` to downsample the old snapshots
+    if e.payload.get("retention").and_then(Value::as_bool) == Some(true) {
+        if retention.is_none() {
+            tracing::info!("No RETENTION_POLICY configured, skipping the retention");
+            return Ok(());
+        }
+        for game_type in [GameType::League, GameType::Tft] {
+            let summoners = lp_db_client.tracking(game_type).list().send().await?;
+            let mut removed = 0;
+            for summoner in &summoners {
+                for queue in Queue::ALL
+                    .into_iter()
+                    .filter(|q| q.game_type() == game_type)
+                {
+                    removed += lp_db_client
+                        .league_points(game_type)
+                        .apply_retention()
+                        .timezone(&Eastern)
+                        .puuid(&summoner.puuid)
+                        .queue(queue)
+                        .send()
+                        .await?;
+                }
+            }
+            tracing::info!("Removed {} old {:?} snapshots", removed, game_type);
+        }
+        return Ok(());
+    }
+    // Invoked manually with `{"summarize_split": "<split id>"}` once a split ended to store the
+    // final rank, peak and games played of every tracked player
+    if let Some(split) = e.payload.get("summarize_split").and_then(Value::as_str) {
+        for game_type in [GameType::League, GameType::Tft] {
+            // Splits are per game
+            if lp_db_client.seasons().find(game_type, split).is_none() {
+                continue;
+            }
+            let summoners = lp_db_client.tracking(game_type).list().send().await?;
+            let mut summarized = 0;
+            for summoner in &summoners {
+                for queue in Queue::ALL
+                    .into_iter()
+                    .filter(|q| q.game_type() == game_type)
+                {
+                    let summary = lp_db_client
+                        .league_points(game_type)
+                        .summarize_split()
+                        .puuid(&summoner.puuid)
+                        .queue(queue)
+                        .split(split)
+                        .send()
+                        .await?;
+                    summarized += summary.is_some() as usize;
+                }
+            }
+            tracing::info!(
+                "Summarized {} {:?} players for {}",
+                summarized,
+                game_type,
+                split
+            );
+        }
+        return Ok(());
+    }
     let league_summs = lp_db_client
         .tracking(GameType::League)
         .list()
@@ -55,14 +286,17 @@ async fn handler(_e: LambdaEvent<Value>) -> Result<(), lambda_runtime::Error> {
         .iter()
         .map(|s| {
             let client = league_client.clone();
-            let id = s.summoner_id();
+            let puuid = s.puuid.clone();
+            // The queues followed by any of the guilds tracking the player
+            let queues = s.tracked_queues();
             tokio::spawn(async move {
                 sleep(Duration::from_millis(100)).await;
-                client
+                let details = client
                     .summoner(summoner::Region::NA1)
-                    .get_league_details(&id)
+                    .get_league_details_by_puuid(&puuid)
                     .send()
-                    .await
+                    .await;
+                (puuid, queues, details)
             })
         })
         .collect::<Vec<_>>();
@@ -70,14 +304,17 @@ async fn handler(_e: LambdaEvent<Value>) -> Result<(), lambda_runtime::Error> {
         .iter()
         .map(|s| {
             let client = tft_client.clone();
-            let id = s.summoner_id();
+            let puuid = s.puuid.clone();
+            // The queues followed by any of the guilds tracking the player
+            let queues = s.tracked_queues();
             tokio::spawn(async move {
                 sleep(Duration::from_millis(100)).await;
-                client
+                let details = client
                     .summoner(summoner::Region::NA1)
-                    .get_league_details(&id)
+                    .get_league_details_by_puuid(&puuid)
                     .send()
-                    .await
+                    .await;
+                (puuid, queues, details)
             })
         })
         .collect::<Vec<_>>();
@@ -86,28 +323,33 @@ async fn handler(_e: LambdaEvent<Value>) -> Result<(), lambda_runtime::Error> {
         futures::future::try_join_all(league_summ_details).await;
     let tft_results: Result<Vec<_>, _> = futures::future::try_join_all(tft_summ_details).await;
 
+    let mut league_snapshots = Vec::new();
+    let mut tft_snapshots = Vec::new();
     match league_results {
         Ok(res) => {
-            for r in res {
+            for (puuid, queues, r) in res {
                 match r {
                     Ok(league) => {
-                        let queue_details =
-                            league.iter().find(|l| l.queue_type == "RANKED_SOLO_5x5");
-                        if let Some(queue_details) = queue_details {
-                            let _ = lp_db_client
-                                .league_points(lp_db::GameType::League)
-                                .add()
-                                .id(&queue_details.summoner_id)
-                                .tier(queue_details.tier.as_ref().unwrap())
-                                .rank(queue_details.rank.as_ref().unwrap())
-                                .timestamp(start_time)
-                                .league_points(queue_details.league_points as i32)
-                                .wins(queue_details.wins)
-                                .losses(queue_details.losses)
-                                .send()
-                                .await;
-                        } else {
-                            tracing::error!("User is not ranked in solo queue, skipping...");
+                        for entry in league {
+                            let Some(queue) = Queue::from_riot_queue_type(&entry.queue_type)
+                                .filter(|queue| queues.contains(queue))
+                            else {
+                                continue;
+                            };
+                            let (Some(tier), Some(rank)) = (&entry.tier, &entry.rank) else {
+                                tracing::error!("User is not ranked in {:?}, skipping...", queue);
+                                continue;
+                            };
+                            league_snapshots.push(LpItem::new(
+                                queue,
+                                &puuid,
+                                start_time,
+                                tier,
+                                rank,
+                                entry.league_points as i32,
+                                entry.wins,
+                                entry.losses,
+                            ));
                         }
                     }
                     Err(e) => {
@@ -128,27 +370,32 @@ async fn handler(_e: LambdaEvent<Value>) -> Result<(), lambda_runtime::Error> {
     }
     match tft_results {
         Ok(res) => {
-            for r in res {
+            for (puuid, queues, r) in res {
                 match r {
                     Ok(league) => {
-                        let queue_details = league.iter().find(|l| l.queue_type == "RANKED_TFT");
-                        if let Some(queue_details) = queue_details {
-                            let _ = lp_db_client
-                                .league_points(lp_db::GameType::Tft)
-                                .add()
-                                .id(&queue_details.summoner_id)
-                                .tier(queue_details.tier.as_ref().unwrap())
-                                .rank(queue_details.rank.as_ref().unwrap())
-                                .timestamp(start_time)
-                                .league_points(
-                                    (*queue_details.league_points.as_ref().unwrap()) as i32,
-                                )
-                                .wins(queue_details.wins)
-                                .losses(queue_details.losses)
-                                .send()
-                                .await;
-                        } else {
-                            tracing::error!("User is not ranked TFT, skipping...");
+                        for entry in league {
+                            let Some(queue) = Queue::from_riot_queue_type(&entry.queue_type)
+                                .filter(|queue| queues.contains(queue))
+                            else {
+                                continue;
+                            };
+                            // Hyper Roll has a rated tier and rating instead of divisions and LP
+                            let tier = entry.tier.as_ref().or(entry.rated_tier.as_ref());
+                            let league_points = entry.league_points.or(entry.rated_rating);
+                            let (Some(tier), Some(league_points)) = (tier, league_points) else {
+                                tracing::error!("User is not ranked in {:?}, skipping...", queue);
+                                continue;
+                            };
+                            tft_snapshots.push(LpItem::new(
+                                queue,
+                                &puuid,
+                                start_time,
+                                tier,
+                                entry.rank.as_deref().unwrap_or_default(),
+                                league_points as i32,
+                                entry.wins,
+                                entry.losses,
+                            ));
                         }
                     }
                     Err(e) => {
@@ -162,6 +409,25 @@ async fn handler(_e: LambdaEvent<Value>) -> Result<(), lambda_runtime::Error> {
         }
     }
 
+    for (game_type, snapshots) in [
+        (GameType::League, league_snapshots),
+        (GameType::Tft, tft_snapshots),
+    ] {
+        let written = lp_db_client
+            .league_points(game_type)
+            .add_many(snapshots.clone())
+            .skip_unchanged_since(unchanged_since)
+            .send()
+            .await;
+        match written {
+            Ok(written) => tracing::info!("Stored {} {:?} snapshots", written, game_type),
+            Err(e) => tracing::error!("Error storing {:?} snapshots: {:?}", game_type, e),
+        }
+        if let Err(e) = record_peaks(&lp_db_client, game_type, start_time, snapshots).await {
+            tracing::error!("Error recording {:?} peak ranks: {:?}", game_type, e);
+        }
+    }
+
     Ok(())
 }
 
@@ -218,10 +484,10 @@ mod test {
         );
         for item in tracking_items {
             println!("{:?}", item);
-            println!("ID for {}: {}", item.summoner_name, item.summoner_id());
+            println!("PUUID for {}: {}", item.summoner_name, item.puuid);
             let league_details = tft_client
                 .summoner(Region::NA1)
-                .get_league_details(&item.summoner_id())
+                .get_league_details_by_puuid(&item.puuid)
                 .send()
                 .await
                 .unwrap();
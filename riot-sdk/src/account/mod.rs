@@ -1,4 +1,4 @@
-use crate::{Error, Handle, Result, ServiceUrl};
+use crate::{Endpoint, Handle, Result, ServiceUrl};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use std::str::FromStr;
@@ -41,6 +41,17 @@ impl AccountClient {
         );
         GetByRiotIdRequestBuilder::new(self.handle.clone(), url)
     }
+    /// Get the Riot ID of a player by PUUID
+    ///
+    /// The PUUID has to be encrypted with the API key of the client.
+    pub fn get_by_puuid(&self, puuid: &str) -> GetByPuuidRequestBuilder {
+        let url = format!(
+            "{}/riot/account/v1/accounts/by-puuid/{}",
+            self.region.base_url(),
+            puuid
+        );
+        GetByPuuidRequestBuilder::new(self.handle.clone(), url)
+    }
 }
 
 pub struct GetByRiotIdRequestBuilder {
@@ -59,20 +70,27 @@ impl GetByRiotIdRequestBuilder {
         }
     }
     pub async fn send(self) -> Result<AccountResponse> {
-        let res = self.handle.web.execute(self.request).await?;
+        self.handle.send(self.request, Endpoint::Account).await
+    }
+}
+
+pub struct GetByPuuidRequestBuilder {
+    request: reqwest::Request,
+    handle: std::sync::Arc<Handle>,
+}
 
-        match res.status() {
-            reqwest::StatusCode::OK => {}
-            reqwest::StatusCode::NOT_FOUND => return Err(Error::SummonerNotFound)?,
-            reqwest::StatusCode::TOO_MANY_REQUESTS => return Err(Error::TooManyRequests)?,
-            reqwest::StatusCode::UNAUTHORIZED => return Err(Error::Unauthorized)?,
-            reqwest::StatusCode::FORBIDDEN => return Err(Error::Forbidden)?,
-            reqwest::StatusCode::BAD_REQUEST => return Err(Error::BadRequest)?,
-            reqwest::StatusCode::INTERNAL_SERVER_ERROR => return Err(Error::RiotError)?,
-            _ => {}
+impl GetByPuuidRequestBuilder {
+    pub fn new(handle: std::sync::Arc<Handle>, url: String) -> Self {
+        Self {
+            handle,
+            request: reqwest::Request::new(
+                reqwest::Method::GET,
+                reqwest::Url::from_str(&url).unwrap(),
+            ),
         }
-        let summoner: AccountResponse = res.json().await?;
-        Ok(summoner)
+    }
+    pub async fn send(self) -> Result<AccountResponse> {
+        self.handle.send(self.request, Endpoint::Account).await
     }
 }
 
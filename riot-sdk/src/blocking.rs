@@ -0,0 +1,371 @@
+//! Synchronous clients for scripts and tools that do not run an async runtime
+//!
+//! Every client owns a single threaded tokio runtime and blocks on it when a request is sent, so
+//! they must not be used from within an async context.
+//!
+//! ```no_run
+//! use riot_sdk::blocking::LeagueClient;
+//! use riot_sdk::summoner::Region;
+//!
+//! let client = LeagueClient::new("RGAPI-...");
+//! let summoner = client
+//!     .summoner(Region::NA1)
+//!     .get_by_puuid("puuid")
+//!     .send()
+//!     .unwrap();
+//! ```
+use crate::account::AccountRegion;
+use crate::cache::{Cache, CachePolicy};
+use crate::matches::{BulkResults, GetManyRequestBuilder, Region as MatchRegion};
+use crate::metrics::Metrics;
+use crate::summoner::Region as SummonerRegion;
+use crate::{account, league, tft, Queue, Result};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// An async client or request builder bound to the runtime of a blocking client
+pub struct Blocking<T> {
+    inner: T,
+    runtime: Arc<Runtime>,
+}
+
+impl<T> Blocking<T> {
+    fn wrap<U>(&self, inner: U) -> Blocking<U> {
+        Blocking {
+            inner,
+            runtime: self.runtime.clone(),
+        }
+    }
+    fn map(self, f: impl FnOnce(T) -> T) -> Self {
+        Self {
+            inner: f(self.inner),
+            runtime: self.runtime,
+        }
+    }
+}
+
+/// Implemented by every async request builder of the SDK
+pub trait AsyncRequest {
+    type Output;
+    fn send(self) -> impl Future<Output = Self::Output>;
+}
+
+macro_rules! async_request {
+    ($($builder:ty => $output:ty),* $(,)?) => {
+        $(
+            impl AsyncRequest for $builder {
+                type Output = $output;
+                fn send(self) -> impl Future<Output = Self::Output> {
+                    <$builder>::send(self)
+                }
+            }
+        )*
+    };
+}
+
+async_request!(
+    account::GetByRiotIdRequestBuilder => Result<account::AccountResponse>,
+    account::GetByPuuidRequestBuilder => Result<account::AccountResponse>,
+    league::summoner::puuid::GetByPuuidRequestBuilder => Result<league::summoner::puuid::SummonerResponse>,
+    league::summoner::league::GetLeagueDetailsRequestBuilder => Result<Vec<league::summoner::league::LeagueResponse>>,
+    league::matches::ids::IdsRequestBuilder => Result<Vec<String>>,
+    league::matches::details::DetailsRequestBuilder => Result<league::matches::details::MatchDetails>,
+    tft::summoner::puuid::GetByPuuidRequestBuilder => Result<tft::summoner::puuid::SummonerResponse>,
+    tft::summoner::league::GetLeagueDetailsRequestBuilder => Result<Vec<tft::summoner::league::LeagueResponse>>,
+    tft::matches::ids::IdsRequestBuilder => Result<Vec<String>>,
+    tft::matches::details::DetailsRequestBuilder => Result<tft::matches::details::TftMatchDetails>,
+);
+
+impl<T: DeserializeOwned> AsyncRequest for GetManyRequestBuilder<T> {
+    type Output = BulkResults<T>;
+    fn send(self) -> impl Future<Output = Self::Output> {
+        GetManyRequestBuilder::send(self)
+    }
+}
+
+impl<T: DeserializeOwned> Blocking<GetManyRequestBuilder<T>> {
+    pub fn concurrency(self, concurrency: usize) -> Self {
+        self.map(|b| b.concurrency(concurrency))
+    }
+}
+
+impl<B: AsyncRequest> Blocking<B> {
+    /// Send the request and block until its response is received
+    pub fn send(self) -> B::Output {
+        self.runtime.block_on(self.inner.send())
+    }
+}
+
+fn runtime() -> Arc<Runtime> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Unable to start the blocking client runtime");
+    Arc::new(runtime)
+}
+
+/// Blocking version of [`crate::AccountClient`]
+#[derive(Clone)]
+pub struct AccountClient {
+    client: Blocking<crate::AccountClient>,
+}
+
+impl AccountClient {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            client: Blocking {
+                inner: crate::AccountClient::new(api_key),
+                runtime: runtime(),
+            },
+        }
+    }
+    pub fn with_cache(self, cache: Arc<dyn Cache>) -> Self {
+        Self {
+            client: self.client.map(|c| c.with_cache(cache)),
+        }
+    }
+    pub fn with_cache_policy(self, cache_policy: CachePolicy) -> Self {
+        Self {
+            client: self.client.map(|c| c.with_cache_policy(cache_policy)),
+        }
+    }
+    pub fn with_metrics(self, metrics: Arc<dyn Metrics>) -> Self {
+        Self {
+            client: self.client.map(|c| c.with_metrics(metrics)),
+        }
+    }
+    pub fn account(&self, region: AccountRegion) -> Blocking<account::AccountClient> {
+        self.client.wrap(self.client.inner.account(region))
+    }
+}
+
+/// Blocking version of [`crate::LeagueClient`]
+#[derive(Clone)]
+pub struct LeagueClient {
+    client: Blocking<crate::LeagueClient>,
+}
+
+impl LeagueClient {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            client: Blocking {
+                inner: crate::LeagueClient::new(api_key),
+                runtime: runtime(),
+            },
+        }
+    }
+    pub fn with_cache(self, cache: Arc<dyn Cache>) -> Self {
+        Self {
+            client: self.client.map(|c| c.with_cache(cache)),
+        }
+    }
+    pub fn with_cache_policy(self, cache_policy: CachePolicy) -> Self {
+        Self {
+            client: self.client.map(|c| c.with_cache_policy(cache_policy)),
+        }
+    }
+    pub fn with_metrics(self, metrics: Arc<dyn Metrics>) -> Self {
+        Self {
+            client: self.client.map(|c| c.with_metrics(metrics)),
+        }
+    }
+    pub fn summoner(&self, region: SummonerRegion) -> Blocking<league::summoner::SummonerClient> {
+        self.client.wrap(self.client.inner.summoner(region))
+    }
+    pub fn matches(&self, region: MatchRegion) -> Blocking<league::matches::MatchClient> {
+        self.client.wrap(self.client.inner.matches(region))
+    }
+}
+
+/// Blocking version of [`crate::TftClient`]
+#[derive(Clone)]
+pub struct TftClient {
+    client: Blocking<crate::TftClient>,
+}
+
+impl TftClient {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            client: Blocking {
+                inner: crate::TftClient::new(api_key),
+                runtime: runtime(),
+            },
+        }
+    }
+    pub fn with_cache(self, cache: Arc<dyn Cache>) -> Self {
+        Self {
+            client: self.client.map(|c| c.with_cache(cache)),
+        }
+    }
+    pub fn with_cache_policy(self, cache_policy: CachePolicy) -> Self {
+        Self {
+            client: self.client.map(|c| c.with_cache_policy(cache_policy)),
+        }
+    }
+    pub fn with_metrics(self, metrics: Arc<dyn Metrics>) -> Self {
+        Self {
+            client: self.client.map(|c| c.with_metrics(metrics)),
+        }
+    }
+    pub fn summoner(&self, region: SummonerRegion) -> Blocking<tft::summoner::SummonerClient> {
+        self.client.wrap(self.client.inner.summoner(region))
+    }
+    pub fn matches(&self, region: MatchRegion) -> Blocking<tft::matches::MatchClient> {
+        self.client.wrap(self.client.inner.matches(region))
+    }
+}
+
+impl<T: Clone> Clone for Blocking<T> {
+    fn clone(&self) -> Self {
+        self.wrap(self.inner.clone())
+    }
+}
+
+impl Blocking<account::AccountClient> {
+    pub fn get_by_riot_id(
+        &self,
+        game_name: &str,
+        tag_line: &str,
+    ) -> Blocking<account::GetByRiotIdRequestBuilder> {
+        self.wrap(self.inner.get_by_riot_id(game_name, tag_line))
+    }
+    pub fn get_by_puuid(&self, puuid: &str) -> Blocking<account::GetByPuuidRequestBuilder> {
+        self.wrap(self.inner.get_by_puuid(puuid))
+    }
+}
+
+impl Blocking<league::summoner::SummonerClient> {
+    pub fn get_by_puuid(
+        &self,
+        puuid: &str,
+    ) -> Blocking<league::summoner::puuid::GetByPuuidRequestBuilder> {
+        self.wrap(self.inner.get_by_puuid(puuid))
+    }
+    pub fn get_league_details(
+        &self,
+        summoner_id: &str,
+    ) -> Blocking<league::summoner::league::GetLeagueDetailsRequestBuilder> {
+        self.wrap(self.inner.get_league_details(summoner_id))
+    }
+    pub fn get_league_details_by_puuid(
+        &self,
+        puuid: &str,
+    ) -> Blocking<league::summoner::league::GetLeagueDetailsRequestBuilder> {
+        self.wrap(self.inner.get_league_details_by_puuid(puuid))
+    }
+}
+
+impl Blocking<league::matches::MatchClient> {
+    pub fn get_details(
+        &self,
+        match_id: &str,
+    ) -> Blocking<league::matches::details::DetailsRequestBuilder> {
+        self.wrap(self.inner.get_details(match_id))
+    }
+    pub fn get_many<S: AsRef<str>>(
+        &self,
+        match_ids: &[S],
+    ) -> Blocking<GetManyRequestBuilder<league::matches::details::MatchDetails>> {
+        self.wrap(self.inner.get_many(match_ids))
+    }
+    pub fn get_ids(
+        &self,
+        summoner_puuid: &str,
+    ) -> Blocking<league::matches::ids::IdsRequestBuilder> {
+        self.wrap(self.inner.get_ids(summoner_puuid))
+    }
+}
+
+impl Blocking<league::matches::ids::IdsRequestBuilder> {
+    pub fn start_time(self, start_time: i64) -> Self {
+        self.map(|b| b.start_time(start_time))
+    }
+    pub fn end_time(self, end_time: i64) -> Self {
+        self.map(|b| b.end_time(end_time))
+    }
+    pub fn queue(self, queue: Queue) -> Self {
+        self.map(|b| b.queue(queue))
+    }
+    pub fn count(self, count: usize) -> Self {
+        self.map(|b| b.count(count))
+    }
+}
+
+impl Blocking<tft::summoner::SummonerClient> {
+    pub fn get_by_puuid(
+        &self,
+        puuid: &str,
+    ) -> Blocking<tft::summoner::puuid::GetByPuuidRequestBuilder> {
+        self.wrap(self.inner.get_by_puuid(puuid))
+    }
+    pub fn get_league_details(
+        &self,
+        summoner_id: &str,
+    ) -> Blocking<tft::summoner::league::GetLeagueDetailsRequestBuilder> {
+        self.wrap(self.inner.get_league_details(summoner_id))
+    }
+    pub fn get_league_details_by_puuid(
+        &self,
+        puuid: &str,
+    ) -> Blocking<tft::summoner::league::GetLeagueDetailsRequestBuilder> {
+        self.wrap(self.inner.get_league_details_by_puuid(puuid))
+    }
+}
+
+impl Blocking<tft::matches::MatchClient> {
+    pub fn get_details(
+        &self,
+        match_id: &str,
+    ) -> Blocking<tft::matches::details::DetailsRequestBuilder> {
+        self.wrap(self.inner.get_details(match_id))
+    }
+    pub fn get_many<S: AsRef<str>>(
+        &self,
+        match_ids: &[S],
+    ) -> Blocking<GetManyRequestBuilder<tft::matches::details::TftMatchDetails>> {
+        self.wrap(self.inner.get_many(match_ids))
+    }
+    pub fn get_ids(&self, summoner_puuid: &str) -> Blocking<tft::matches::ids::IdsRequestBuilder> {
+        self.wrap(self.inner.get_ids(summoner_puuid))
+    }
+}
+
+impl Blocking<tft::matches::ids::IdsRequestBuilder> {
+    pub fn start_time(self, start_time: i64) -> Self {
+        self.map(|b| b.start_time(start_time))
+    }
+    pub fn end_time(self, end_time: i64) -> Self {
+        self.map(|b| b.end_time(end_time))
+    }
+    pub fn count(self, count: usize) -> Self {
+        self.map(|b| b.count(count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+
+    #[test]
+    fn send_from_cache() {
+        let cache = Arc::new(InMemoryCache::new());
+        let client = AccountClient::new("RGAPI-test").with_cache(cache.clone());
+        let url =
+            "https://americas.api.riotgames.com/riot/account/v1/accounts/by-riot-id/monke/NA1";
+        client.client.runtime.block_on(cache.set(
+            url,
+            br#"{"puuid":"abc","gameName":"monke","tagLine":"NA1"}"#.to_vec(),
+            None,
+        ));
+
+        let account = client
+            .account(AccountRegion::AMERICAS)
+            .get_by_riot_id("monke", "NA1")
+            .send()
+            .unwrap();
+        assert_eq!(account.puuid, "abc");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::Endpoint;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a response is kept in the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheTtl {
+    /// The response is never cached
+    Skip,
+    /// The response expires after the given duration
+    Expire(Duration),
+    /// The response never expires (immutable resources)
+    Forever,
+}
+
+/// Per-endpoint TTLs used by a client cache
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    ttls: HashMap<Endpoint, CacheTtl>,
+}
+
+impl CachePolicy {
+    /// Override the TTL of an endpoint
+    pub fn ttl(mut self, endpoint: Endpoint, ttl: CacheTtl) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+    pub fn get(&self, endpoint: Endpoint) -> CacheTtl {
+        self.ttls.get(&endpoint).copied().unwrap_or(CacheTtl::Skip)
+    }
+}
+
+impl Default for CachePolicy {
+    /// Match details are immutable and kept forever, league entries change after every game so
+    /// they are only kept for a minute. Match ids are never cached as new games show up at any
+    /// time.
+    fn default() -> Self {
+        let ttls = HashMap::from([
+            (
+                Endpoint::Account,
+                CacheTtl::Expire(Duration::from_secs(60 * 60)),
+            ),
+            (
+                Endpoint::Summoner,
+                CacheTtl::Expire(Duration::from_secs(60 * 60)),
+            ),
+            (
+                Endpoint::LeagueEntries,
+                CacheTtl::Expire(Duration::from_secs(60)),
+            ),
+            (Endpoint::MatchIds, CacheTtl::Skip),
+            (Endpoint::MatchDetails, CacheTtl::Forever),
+        ]);
+        Self { ttls }
+    }
+}
+
+/// Storage for raw Riot API response bodies
+///
+/// Keys are the full request URLs. Implement this trait to plug a persistent backend (DynamoDB,
+/// Redis...) into a client with `with_cache`.
+#[async_trait]
+pub trait Cache: Debug + Send + Sync {
+    /// Get a cached response body, `None` when missing or expired
+    async fn get(&self, key: &str) -> Option<Vec<u8>>;
+    /// Store a response body, `ttl` is `None` when the entry never expires
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Option<Duration>);
+}
+
+/// In-memory [`Cache`] shared by every clone of a client
+///
+/// Expired entries are evicted when they are read.
+#[derive(Debug, Default)]
+pub struct InMemoryCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    value: Vec<u8>,
+    expires_at: Option<Instant>,
+}
+
+impl InMemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Cache for InMemoryCache {
+    async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let mut entries = self.entries.lock().expect("cache lock poisoned");
+        match entries.get(key) {
+            Some(entry) if entry.expires_at.is_none_or(|e| e > Instant::now()) => {
+                Some(entry.value.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Option<Duration>) {
+        let entry = CacheEntry {
+            value,
+            expires_at: ttl.map(|ttl| Instant::now() + ttl),
+        };
+        self.entries
+            .lock()
+            .expect("cache lock poisoned")
+            .insert(key.to_string(), entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy() {
+        let policy = CachePolicy::default();
+        assert_eq!(policy.get(Endpoint::MatchDetails), CacheTtl::Forever);
+        assert_eq!(policy.get(Endpoint::MatchIds), CacheTtl::Skip);
+        assert_eq!(
+            policy.get(Endpoint::LeagueEntries),
+            CacheTtl::Expire(Duration::from_secs(60))
+        );
+    }
+    #[tokio::test]
+    async fn in_memory_expiry() {
+        let cache = InMemoryCache::new();
+        cache.set("forever", b"a".to_vec(), None).await;
+        cache
+            .set("expired", b"b".to_vec(), Some(Duration::ZERO))
+            .await;
+
+        assert_eq!(cache.get("forever").await, Some(b"a".to_vec()));
+        assert_eq!(cache.get("expired").await, None);
+        assert_eq!(cache.get("missing").await, None);
+    }
+}
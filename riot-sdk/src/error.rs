@@ -0,0 +1,207 @@
+use crate::Endpoint;
+use serde::Deserialize;
+use std::fmt::Display;
+use std::time::Duration;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Longest part of an undecodable body kept in [`Error::Decode`]
+const BODY_SNIPPET_LEN: usize = 200;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Riot answered 404, `resource` is the endpoint that was queried (a summoner, a match...)
+    NotFound { resource: Endpoint },
+    /// Riot answered 429
+    RateLimited {
+        retry_after: Option<Duration>,
+        scope: RateLimitScope,
+    },
+    /// Any other unsuccessful status, with the message of the Riot error body when present
+    Status { code: u16, message: Option<String> },
+    /// The response body does not match the expected type
+    Decode {
+        endpoint: Endpoint,
+        body_snippet: String,
+        source: serde_json::Error,
+    },
+    /// The request could not be sent or its body could not be read
+    Transport(reqwest::Error),
+}
+
+/// Which rate limit a 429 was caused by, from the `X-Rate-Limit-Type` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitScope {
+    /// The limit of the API key
+    Application,
+    /// The limit of the endpoint for the API key
+    Method,
+    /// The limit of the underlying Riot service, shared by every API key
+    Service,
+    Unknown,
+}
+
+impl RateLimitScope {
+    pub(crate) fn parse(header: Option<&str>) -> Self {
+        match header {
+            Some("application") => RateLimitScope::Application,
+            Some("method") => RateLimitScope::Method,
+            Some("service") => RateLimitScope::Service,
+            _ => RateLimitScope::Unknown,
+        }
+    }
+}
+
+impl Display for RateLimitScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scope = match self {
+            RateLimitScope::Application => "application",
+            RateLimitScope::Method => "method",
+            RateLimitScope::Service => "service",
+            RateLimitScope::Unknown => "unknown",
+        };
+        write!(f, "{}", scope)
+    }
+}
+
+impl Error {
+    /// Build the error of an unsuccessful response from its status and body
+    pub(crate) fn from_status(
+        code: u16,
+        endpoint: Endpoint,
+        body: &[u8],
+        retry_after: Option<Duration>,
+        scope: Option<&str>,
+    ) -> Self {
+        match code {
+            404 => Error::NotFound { resource: endpoint },
+            429 => Error::RateLimited {
+                retry_after,
+                scope: RateLimitScope::parse(scope),
+            },
+            _ => Error::Status {
+                code,
+                message: status_message(body),
+            },
+        }
+    }
+    pub(crate) fn decode(endpoint: Endpoint, body: &[u8], source: serde_json::Error) -> Self {
+        let body = String::from_utf8_lossy(body);
+        let body_snippet = match body.char_indices().nth(BODY_SNIPPET_LEN) {
+            Some((end, _)) => format!("{}...", &body[..end]),
+            None => body.into_owned(),
+        };
+        Error::Decode {
+            endpoint,
+            body_snippet,
+            source,
+        }
+    }
+}
+
+/// Riot error bodies look like `{"status": {"message": "...", "status_code": 403}}`
+#[derive(Deserialize)]
+struct ErrorBody {
+    status: ErrorStatus,
+}
+
+#[derive(Deserialize)]
+struct ErrorStatus {
+    message: String,
+}
+
+fn status_message(body: &[u8]) -> Option<String> {
+    serde_json::from_slice::<ErrorBody>(body)
+        .ok()
+        .map(|b| b.status.message)
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Decode { source, .. } => Some(source),
+            Error::Transport(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NotFound { resource } => write!(f, "{} not found", resource),
+            Error::RateLimited {
+                retry_after: Some(retry_after),
+                scope,
+            } => write!(
+                f,
+                "Rate limited ({} limit), retry after {}s",
+                scope,
+                retry_after.as_secs()
+            ),
+            Error::RateLimited {
+                retry_after: None,
+                scope,
+            } => write!(f, "Rate limited ({} limit)", scope),
+            Error::Status {
+                code,
+                message: Some(message),
+            } => write!(f, "Riot API error {}: {}", code, message),
+            Error::Status {
+                code,
+                message: None,
+            } => write!(f, "Riot API error {}", code),
+            Error::Decode { endpoint, .. } => {
+                write!(f, "Unable to decode {} response", endpoint)
+            }
+            Error::Transport(e) => e.fmt(f),
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Transport(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn riot_error_body() {
+        let body = br#"{"status": {"message": "Forbidden", "status_code": 403}}"#;
+        match Error::from_status(403, Endpoint::Summoner, body, None, None) {
+            Error::Status { code, message } => {
+                assert_eq!(code, 403);
+                assert_eq!(message.as_deref(), Some("Forbidden"));
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+        assert!(matches!(
+            Error::from_status(404, Endpoint::MatchDetails, b"", None, None),
+            Error::NotFound {
+                resource: Endpoint::MatchDetails
+            }
+        ));
+        assert!(matches!(
+            Error::from_status(429, Endpoint::Summoner, b"", None, Some("service")),
+            Error::RateLimited {
+                scope: RateLimitScope::Service,
+                ..
+            }
+        ));
+    }
+    #[test]
+    fn decode_snippet() {
+        let body = "é".repeat(BODY_SNIPPET_LEN + 10);
+        let source = serde_json::from_str::<u32>("x").unwrap_err();
+        match Error::decode(Endpoint::MatchIds, body.as_bytes(), source) {
+            Error::Decode { body_snippet, .. } => {
+                assert_eq!(body_snippet.chars().count(), BODY_SNIPPET_LEN + 3)
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Error, Handle, Result};
+use crate::{Endpoint, Handle, Result};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -18,18 +18,7 @@ impl DetailsRequestBuilder {
         }
     }
     pub async fn send(self) -> Result<MatchDetails> {
-        let res = self.handle.web.execute(self.request).await?;
-        match res.status() {
-            reqwest::StatusCode::NOT_FOUND => return Err(Error::SummonerNotFound)?,
-            reqwest::StatusCode::TOO_MANY_REQUESTS => return Err(Error::TooManyRequests)?,
-            reqwest::StatusCode::UNAUTHORIZED => return Err(Error::Unauthorized)?,
-            reqwest::StatusCode::FORBIDDEN => return Err(Error::Forbidden)?,
-            reqwest::StatusCode::BAD_REQUEST => return Err(Error::BadRequest)?,
-            reqwest::StatusCode::INTERNAL_SERVER_ERROR => return Err(Error::RiotError)?,
-            _ => {}
-        }
-        let match_ids: MatchDetails = res.json().await?;
-        Ok(match_ids)
+        self.handle.send(self.request, Endpoint::MatchDetails).await
     }
 }
 
@@ -1,5 +1,5 @@
 use self::ids::IdsRequestBuilder;
-use crate::matches::Region;
+use crate::matches::{GetManyRequestBuilder, Region};
 use crate::{Handle, ServiceUrl};
 
 pub mod details;
@@ -21,6 +21,21 @@ impl MatchClient {
         );
         details::DetailsRequestBuilder::new(self.handle.clone(), url)
     }
+    /// Get the details of many matches, see [`GetManyRequestBuilder`]
+    pub fn get_many<S: AsRef<str>>(
+        &self,
+        match_ids: &[S],
+    ) -> GetManyRequestBuilder<details::MatchDetails> {
+        let requests = match_ids
+            .iter()
+            .map(|id| {
+                let id = id.as_ref();
+                let url = format!("{}/lol/match/v5/matches/{}", self.region.base_url(), id);
+                (id.to_string(), url)
+            })
+            .collect();
+        GetManyRequestBuilder::new(self.handle.clone(), requests)
+    }
     pub fn get_ids(&self, summoner_puuid: &str) -> IdsRequestBuilder {
         let url = format!(
             "{}/lol/match/v5/matches/by-puuid/{}/ids",
@@ -44,4 +44,13 @@ impl SummonerClient {
         );
         GetLeagueDetailsRequestBuilder::new(self.handle.clone(), url)
     }
+    /// Get league entries in all queues for a given PUUID
+    pub fn get_league_details_by_puuid(&self, puuid: &str) -> GetLeagueDetailsRequestBuilder {
+        let url = format!(
+            "{}/lol/league/v4/entries/by-puuid/{}",
+            self.region.base_url(),
+            puuid
+        );
+        GetLeagueDetailsRequestBuilder::new(self.handle.clone(), url)
+    }
 }
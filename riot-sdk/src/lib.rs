@@ -1,55 +1,38 @@
 pub mod account;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod error;
 pub mod league;
 pub mod matches;
+pub mod metrics;
+mod rate_limit;
 pub mod summoner;
 pub mod tft;
 
+use crate::cache::{Cache, CachePolicy, CacheTtl};
 use crate::matches::Region as MatchRegion;
+use crate::metrics::{Metrics, NoopMetrics, RequestEvent};
+use crate::rate_limit::RateLimiter;
 use crate::summoner::Region as SummonerRegion;
 use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
 use std::fmt::Display;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::field::Empty;
+use tracing::Instrument;
 
 use self::account::AccountRegion;
+pub use self::error::{Error, Result};
 
-pub type Result<T> = core::result::Result<T, Error>;
-
-#[derive(Debug)]
-pub enum Error {
-    HttpError(reqwest::Error),
-    SummonerNotFound,
-    TooManyRequests,
-    Forbidden,
-    Unauthorized,
-    RiotError,
-    BadRequest,
-}
-
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let msg = match self {
-            Error::HttpError(e) => return e.fmt(f),
-            Error::SummonerNotFound => "Summoner not found",
-            Error::TooManyRequests => "Too many requests",
-            Error::Forbidden => "API key is invalid",
-            Error::Unauthorized => "Unauthorized",
-            Error::RiotError => "Riot API error",
-            Error::BadRequest => "Bad request to Riot API (likely an error on their end)",
-        };
-        write!(f, "{}", msg)
-    }
-}
-impl From<reqwest::Error> for Error {
-    fn from(e: reqwest::Error) -> Self {
-        Error::HttpError(e)
-    }
-}
 #[derive(Debug, Clone)]
 pub enum Queue {
     RankedSolo5x5,
     RankedFlex5x5,
     TFTRanked,
     TFTHyperRoll,
+    TFTDoubleUp,
     TFTNormal,
 }
 
@@ -60,6 +43,7 @@ impl Queue {
             Queue::RankedFlex5x5 => "Ranked Flex",
             Queue::TFTRanked => "Ranked",
             Queue::TFTHyperRoll => "Hyper Roll",
+            Queue::TFTDoubleUp => "Double Up",
             Queue::TFTNormal => "Normal",
         }
     }
@@ -71,6 +55,7 @@ impl Display for Queue {
             Queue::RankedFlex5x5 => "RANKED_FLEX",
             Queue::TFTRanked => "RANKED_TFT",
             Queue::TFTHyperRoll => "RANKED_TFT_HYPERROLL",
+            Queue::TFTDoubleUp => "RANKED_TFT_DOUBLE_UP",
             Queue::TFTNormal => "NORMAL_TFT",
         };
         write!(f, "{}", queue)
@@ -83,6 +68,7 @@ impl From<i64> for Queue {
             440 => Queue::RankedFlex5x5,
             1100 => Queue::TFTRanked,
             1130 => Queue::TFTHyperRoll,
+            1160 => Queue::TFTDoubleUp,
             1090 => Queue::TFTNormal,
             _ => panic!("Unknown queue type {}", queue),
         }
@@ -95,6 +81,7 @@ impl From<Queue> for i64 {
             Queue::RankedFlex5x5 => 440,
             Queue::TFTRanked => 1100,
             Queue::TFTHyperRoll => 1130,
+            Queue::TFTDoubleUp => 1160,
             Queue::TFTNormal => 1090,
         }
     }
@@ -107,6 +94,7 @@ impl From<&Queue> for GameType {
             Queue::RankedFlex5x5 => GameType::Ranked,
             Queue::TFTRanked => GameType::Ranked,
             Queue::TFTHyperRoll => GameType::Ranked,
+            Queue::TFTDoubleUp => GameType::Ranked,
             Queue::TFTNormal => GameType::Normal,
         }
     }
@@ -119,6 +107,7 @@ impl From<Queue> for GameType {
             Queue::RankedFlex5x5 => GameType::Ranked,
             Queue::TFTRanked => GameType::Ranked,
             Queue::TFTHyperRoll => GameType::Ranked,
+            Queue::TFTDoubleUp => GameType::Ranked,
             Queue::TFTNormal => GameType::Normal,
         }
     }
@@ -142,17 +131,47 @@ impl Display for GameType {
         write!(f, "{}", game_type)
     }
 }
-#[derive(Debug)]
+/// The Riot API endpoints a response can come from
+///
+/// Used to pick the cache TTL of a response and to label telemetry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// account-v1 lookups by Riot ID
+    Account,
+    /// summoner lookups by name or PUUID
+    Summoner,
+    /// league entries of a summoner
+    LeagueEntries,
+    /// match ids of a player
+    MatchIds,
+    /// details of a single match
+    MatchDetails,
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let endpoint = match self {
+            Endpoint::Account => "Riot account",
+            Endpoint::Summoner => "Summoner",
+            Endpoint::LeagueEntries => "League entries",
+            Endpoint::MatchIds => "Match history",
+            Endpoint::MatchDetails => "Match",
+        };
+        write!(f, "{}", endpoint)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Handle {
     pub web: reqwest::Client,
-}
-/// Client for interacting with the Riot Account APIs
-pub struct AccountClient {
-    handle: Arc<Handle>,
+    pub cache: Option<Arc<dyn Cache>>,
+    pub cache_policy: CachePolicy,
+    pub metrics: Arc<dyn Metrics>,
+    rate_limiter: Arc<RateLimiter>,
 }
 
-impl AccountClient {
-    pub fn new(api_key: &str) -> Self {
+impl Handle {
+    fn new(api_key: &str) -> Self {
         let mut shared_headers = HeaderMap::new();
         shared_headers.insert(
             "X-Riot-Token",
@@ -163,7 +182,167 @@ impl AccountClient {
             .build()
             .expect("No TLS backend found");
         Self {
-            handle: Arc::new(Handle { web: client }),
+            web: client,
+            cache: None,
+            cache_policy: CachePolicy::default(),
+            metrics: Arc::new(NoopMetrics),
+            rate_limiter: Arc::new(RateLimiter::default()),
+        }
+    }
+    /// Execute a request and deserialize its body, going through the cache when one is set
+    ///
+    /// Every request is wrapped in a `riot_request` span.
+    pub async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::Request,
+        endpoint: Endpoint,
+    ) -> Result<T> {
+        let span = tracing::info_span!(
+            "riot_request",
+            endpoint = ?endpoint,
+            routing = request.url().host_str().unwrap_or_default(),
+            status = Empty,
+            latency_ms = Empty,
+            rate_limit_remaining = Empty,
+            cache_hit = Empty,
+        );
+        self.send_instrumented(request, endpoint, &span)
+            .instrument(span.clone())
+            .await
+    }
+    async fn send_instrumented<T: DeserializeOwned>(
+        &self,
+        request: reqwest::Request,
+        endpoint: Endpoint,
+        span: &tracing::Span,
+    ) -> Result<T> {
+        let started = Instant::now();
+        let mut event = RequestEvent {
+            endpoint,
+            status: None,
+            latency: Duration::ZERO,
+            cache_hit: false,
+            rate_limit_remaining: None,
+        };
+        let ttl = self.cache_policy.get(endpoint);
+        let cache = match ttl {
+            CacheTtl::Skip => None,
+            _ => self.cache.as_ref(),
+        };
+        let key = request.url().to_string();
+
+        if let Some(cache) = cache {
+            if let Some(body) = cache.get(&key).await {
+                // An entry that no longer matches the response type is treated as a miss
+                if let Ok(value) = serde_json::from_slice(&body) {
+                    event.cache_hit = true;
+                    self.finish(&mut event, started, span);
+                    return Ok(value);
+                }
+            }
+        }
+
+        self.rate_limiter.ready().await;
+        let res = match self.web.execute(request).await {
+            Ok(res) => res,
+            Err(e) => {
+                self.finish(&mut event, started, span);
+                return Err(e.into());
+            }
+        };
+        let status = res.status();
+        let retry_after = rate_limit::retry_after(res.headers());
+        if let (true, Some(delay)) = (
+            status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                || status == reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            retry_after,
+        ) {
+            // Hold back every request sharing this handle, not only this one
+            self.rate_limiter.block_for(delay);
+        }
+        event.status = Some(status.as_u16());
+        event.rate_limit_remaining = rate_limit::remaining(res.headers());
+
+        let scope = res
+            .headers()
+            .get("X-Rate-Limit-Type")
+            .and_then(|scope| scope.to_str().ok())
+            .map(str::to_string);
+        // The latency includes the download of the body
+        let body = res.bytes().await;
+        self.finish(&mut event, started, span);
+        let body = body?;
+        if !status.is_success() {
+            return Err(Error::from_status(
+                status.as_u16(),
+                endpoint,
+                &body,
+                retry_after,
+                scope.as_deref(),
+            ));
+        }
+        let value = serde_json::from_slice(&body).map_err(|e| Error::decode(endpoint, &body, e))?;
+
+        if let Some(cache) = cache {
+            let ttl = match ttl {
+                CacheTtl::Expire(ttl) => Some(ttl),
+                _ => None,
+            };
+            cache.set(&key, body.to_vec(), ttl).await;
+        }
+        Ok(value)
+    }
+    /// Record the outcome of a request on its span and in the metrics
+    fn finish(&self, event: &mut RequestEvent, started: Instant, span: &tracing::Span) {
+        event.latency = started.elapsed();
+        span.record("latency_ms", event.latency.as_millis() as u64);
+        span.record("cache_hit", event.cache_hit);
+        if let Some(status) = event.status {
+            span.record("status", status);
+        }
+        if let Some(remaining) = event.rate_limit_remaining {
+            span.record("rate_limit_remaining", remaining);
+        }
+        tracing::debug!("Riot API request completed");
+        self.metrics.request(event);
+    }
+}
+/// Client for interacting with the Riot Account APIs
+pub struct AccountClient {
+    handle: Arc<Handle>,
+}
+
+impl AccountClient {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            handle: Arc::new(Handle::new(api_key)),
+        }
+    }
+    /// Cache responses in the given cache
+    ///
+    /// Cache keys are request URLs, so a cache should not be shared with a client using another
+    /// API key as PUUIDs are encrypted per key.
+    pub fn with_cache(self, cache: Arc<dyn Cache>) -> Self {
+        let mut handle = (*self.handle).clone();
+        handle.cache = Some(cache);
+        Self {
+            handle: Arc::new(handle),
+        }
+    }
+    /// Override the per-endpoint cache TTLs
+    pub fn with_cache_policy(self, cache_policy: CachePolicy) -> Self {
+        let mut handle = (*self.handle).clone();
+        handle.cache_policy = cache_policy;
+        Self {
+            handle: Arc::new(handle),
+        }
+    }
+    /// Report request counters to the given metrics sink
+    pub fn with_metrics(self, metrics: Arc<dyn Metrics>) -> Self {
+        let mut handle = (*self.handle).clone();
+        handle.metrics = metrics;
+        Self {
+            handle: Arc::new(handle),
         }
     }
     pub fn account(&self, region: AccountRegion) -> account::AccountClient {
@@ -185,17 +364,35 @@ pub struct LeagueClient {
 
 impl LeagueClient {
     pub fn new(api_key: &str) -> Self {
-        let mut shared_headers = HeaderMap::new();
-        shared_headers.insert(
-            "X-Riot-Token",
-            api_key.parse().expect("Invalid API key format"),
-        );
-        let client = reqwest::Client::builder()
-            .default_headers(shared_headers)
-            .build()
-            .expect("No TLS backend found");
         Self {
-            handle: Arc::new(Handle { web: client }),
+            handle: Arc::new(Handle::new(api_key)),
+        }
+    }
+    /// Cache responses in the given cache
+    ///
+    /// Cache keys are request URLs, so a cache should not be shared with a client using another
+    /// API key as PUUIDs are encrypted per key.
+    pub fn with_cache(self, cache: Arc<dyn Cache>) -> Self {
+        let mut handle = (*self.handle).clone();
+        handle.cache = Some(cache);
+        Self {
+            handle: Arc::new(handle),
+        }
+    }
+    /// Override the per-endpoint cache TTLs
+    pub fn with_cache_policy(self, cache_policy: CachePolicy) -> Self {
+        let mut handle = (*self.handle).clone();
+        handle.cache_policy = cache_policy;
+        Self {
+            handle: Arc::new(handle),
+        }
+    }
+    /// Report request counters to the given metrics sink
+    pub fn with_metrics(self, metrics: Arc<dyn Metrics>) -> Self {
+        let mut handle = (*self.handle).clone();
+        handle.metrics = metrics;
+        Self {
+            handle: Arc::new(handle),
         }
     }
 
@@ -222,17 +419,35 @@ pub struct TftClient {
 
 impl TftClient {
     pub fn new(api_key: &str) -> Self {
-        let mut shared_headers = HeaderMap::new();
-        shared_headers.insert(
-            "X-Riot-Token",
-            api_key.parse().expect("Invalid API key format"),
-        );
-        let client = reqwest::Client::builder()
-            .default_headers(shared_headers)
-            .build()
-            .expect("No TLS backend found");
         Self {
-            handle: Arc::new(Handle { web: client }),
+            handle: Arc::new(Handle::new(api_key)),
+        }
+    }
+    /// Cache responses in the given cache
+    ///
+    /// Cache keys are request URLs, so a cache should not be shared with a client using another
+    /// API key as PUUIDs are encrypted per key.
+    pub fn with_cache(self, cache: Arc<dyn Cache>) -> Self {
+        let mut handle = (*self.handle).clone();
+        handle.cache = Some(cache);
+        Self {
+            handle: Arc::new(handle),
+        }
+    }
+    /// Override the per-endpoint cache TTLs
+    pub fn with_cache_policy(self, cache_policy: CachePolicy) -> Self {
+        let mut handle = (*self.handle).clone();
+        handle.cache_policy = cache_policy;
+        Self {
+            handle: Arc::new(handle),
+        }
+    }
+    /// Report request counters to the given metrics sink
+    pub fn with_metrics(self, metrics: Arc<dyn Metrics>) -> Self {
+        let mut handle = (*self.handle).clone();
+        handle.metrics = metrics;
+        Self {
+            handle: Arc::new(handle),
         }
     }
 
@@ -309,6 +524,11 @@ impl PlayerRank {
         format!("**{}** {} LP", division_str, self.league_points)
     }
     pub fn parse_str(division: &str, rank: &str, league_points: i32) -> Self {
+        Self::try_parse_str(division, rank, league_points).expect("Invalid division or rank")
+    }
+    /// Parse the tier and rank of a league entry, `None` when they are not a ranked division
+    /// (e.g. the rated tiers of Hyper Roll)
+    pub fn try_parse_str(division: &str, rank: &str, league_points: i32) -> Option<Self> {
         let division = match division {
             "IRON" => Division::Iron,
             "BRONZE" => Division::Bronze,
@@ -320,7 +540,7 @@ impl PlayerRank {
             "MASTER" => Division::Master,
             "GRANDMASTER" => Division::Grandmaster,
             "CHALLENGER" => Division::Challenger,
-            _ => panic!("Invalid division"),
+            _ => return None,
         };
 
         let rank = match rank {
@@ -328,14 +548,14 @@ impl PlayerRank {
             "II" => Rank::II,
             "III" => Rank::III,
             "IV" => Rank::IV,
-            _ => panic!("Invalid rank"),
+            _ => return None,
         };
 
-        PlayerRank {
+        Some(PlayerRank {
             division,
             rank,
             league_points,
-        }
+        })
     }
     pub fn new(division: Division, rank: Rank, league_points: i32) -> Self {
         PlayerRank {
@@ -1,5 +1,9 @@
-use crate::ServiceUrl;
+use crate::{Endpoint, Error, Handle, Result, ServiceUrl};
+use futures::stream::{self, StreamExt};
+use serde::de::DeserializeOwned;
 use std::fmt::Display;
+use std::marker::PhantomData;
+use std::str::FromStr;
 
 pub enum Region {
     AMERICAS,
@@ -20,3 +24,114 @@ impl Display for Region {
     }
 }
 impl ServiceUrl for Region {}
+
+/// Fetch the details of many matches with bounded concurrency
+///
+/// Created by `MatchClient::get_many` of either game.
+pub struct GetManyRequestBuilder<T> {
+    handle: std::sync::Arc<Handle>,
+    /// The match ids with their details URL
+    requests: Vec<(String, String)>,
+    concurrency: Option<usize>,
+    details: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> GetManyRequestBuilder<T> {
+    pub(crate) fn new(handle: std::sync::Arc<Handle>, requests: Vec<(String, String)>) -> Self {
+        Self {
+            handle,
+            requests,
+            concurrency: None,
+            details: PhantomData,
+        }
+    }
+    /// Set how many requests are in flight at once.
+    ///
+    /// Defaults to 5
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = Some(concurrency.max(1));
+        self
+    }
+    pub async fn send(self) -> BulkResults<T> {
+        let concurrency = self.concurrency.unwrap_or(5);
+        let handle = self.handle;
+
+        let results = stream::iter(self.requests)
+            .map(|(id, url)| {
+                let handle = handle.clone();
+                async move {
+                    let request = reqwest::Request::new(
+                        reqwest::Method::GET,
+                        reqwest::Url::from_str(&url).unwrap(),
+                    );
+                    let res = handle.send(request, Endpoint::MatchDetails).await;
+                    (id, res)
+                }
+            })
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+        BulkResults { results }
+    }
+}
+
+/// Per-id results of a bulk fetch, in the order the ids were given
+#[derive(Debug)]
+pub struct BulkResults<T> {
+    pub results: Vec<(String, Result<T>)>,
+}
+
+impl<T> BulkResults<T> {
+    /// Whether every id was fetched successfully
+    pub fn is_complete(&self) -> bool {
+        self.results.iter().all(|(_, res)| res.is_ok())
+    }
+    /// The ids that could not be fetched
+    pub fn failed_ids(&self) -> Vec<&str> {
+        self.results
+            .iter()
+            .filter(|(_, res)| res.is_err())
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+    /// Split the results into the fetched values and the failed ids with their error
+    pub fn partition(self) -> (Vec<T>, Vec<(String, Error)>) {
+        let mut values = Vec::new();
+        let mut failures = Vec::new();
+        for (id, res) in self.results {
+            match res {
+                Ok(value) => values.push(value),
+                Err(e) => failures.push((id, e)),
+            }
+        }
+        (values, failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_keeps_order() {
+        let results = BulkResults {
+            results: vec![
+                ("NA1_1".to_string(), Ok(1)),
+                (
+                    "NA1_2".to_string(),
+                    Err(Error::NotFound {
+                        resource: Endpoint::MatchDetails,
+                    }),
+                ),
+                ("NA1_3".to_string(), Ok(3)),
+            ],
+        };
+        assert!(!results.is_complete());
+        assert_eq!(results.failed_ids(), vec!["NA1_2"]);
+
+        let (values, failures) = results.partition();
+        assert_eq!(values, vec![1, 3]);
+        assert_eq!(failures[0].0, "NA1_2");
+    }
+}
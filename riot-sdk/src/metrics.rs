@@ -0,0 +1,33 @@
+use crate::Endpoint;
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// Outcome of a request sent through a client
+#[derive(Debug, Clone)]
+pub struct RequestEvent {
+    pub endpoint: Endpoint,
+    /// The HTTP status of the response, `None` on a cache hit or a transport error
+    pub status: Option<u16>,
+    /// Until the body of the response has been read
+    pub latency: Duration,
+    pub cache_hit: bool,
+    /// Requests left in the tightest Riot rate limit window, when reported
+    pub rate_limit_remaining: Option<u32>,
+}
+
+/// Counters updated by every client request
+///
+/// Implement this trait to forward the counters to CloudWatch, Prometheus... Every method
+/// defaults to doing nothing.
+pub trait Metrics: Debug + Send + Sync {
+    /// Called once per request, after the body of the response has been read or the cache was hit
+    fn request(&self, event: &RequestEvent) {
+        let _ = event;
+    }
+}
+
+/// [`Metrics`] implementation discarding every counter
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
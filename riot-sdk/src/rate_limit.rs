@@ -0,0 +1,115 @@
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Holds back requests after Riot answered with a `Retry-After`
+///
+/// Shared by every clone of a client so concurrent requests wait together instead of all
+/// hitting the limit again.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimiter {
+    blocked_until: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Wait until the last reported rate limit has expired
+    pub(crate) async fn ready(&self) {
+        let blocked_until = *self
+            .blocked_until
+            .lock()
+            .expect("rate limiter lock poisoned");
+        if let Some(blocked_until) = blocked_until {
+            if blocked_until > Instant::now() {
+                tokio::time::sleep_until(blocked_until.into()).await;
+            }
+        }
+    }
+    /// Block requests for `delay`, unless they are already blocked for longer
+    pub(crate) fn block_for(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut blocked_until = self
+            .blocked_until
+            .lock()
+            .expect("rate limiter lock poisoned");
+        if blocked_until.is_none_or(|current| current < until) {
+            *blocked_until = Some(until);
+        }
+    }
+}
+
+/// Get the `Retry-After` delay of a response
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let secs = headers.get("Retry-After")?.to_str().ok()?.parse().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+/// Get the requests left in the tightest application or method rate limit window
+///
+/// Either pair of headers may be missing, e.g. responses served without an application limit.
+pub(crate) fn remaining(headers: &HeaderMap) -> Option<u32> {
+    let scope_remaining = |limits: &str, counts: &str| {
+        let limits = headers.get(limits)?.to_str().ok()?;
+        let counts = headers.get(counts)?.to_str().ok()?;
+        window_remaining(limits, counts)
+    };
+    let app = scope_remaining("X-App-Rate-Limit", "X-App-Rate-Limit-Count");
+    let method = scope_remaining("X-Method-Rate-Limit", "X-Method-Rate-Limit-Count");
+    match (app, method) {
+        (Some(app), Some(method)) => Some(app.min(method)),
+        (app, method) => app.or(method),
+    }
+}
+
+/// Riot reports limits as `<requests>:<seconds>` windows, e.g. `20:1,100:120`
+fn window_remaining(limits: &str, counts: &str) -> Option<u32> {
+    let counts = counts
+        .split(',')
+        .filter_map(|window| {
+            let (count, secs) = window.split_once(':')?;
+            Some((secs, count.parse::<u32>().ok()?))
+        })
+        .collect::<HashMap<_, _>>();
+
+    limits
+        .split(',')
+        .filter_map(|window| {
+            let (limit, secs) = window.split_once(':')?;
+            let limit = limit.parse::<u32>().ok()?;
+            Some(limit.saturating_sub(*counts.get(secs)?))
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tightest_window() {
+        assert_eq!(window_remaining("20:1,100:120", "1:1,95:120"), Some(5));
+        assert_eq!(window_remaining("20:1,100:120", "20:1,2:120"), Some(0));
+        assert_eq!(window_remaining("20:1", ""), None);
+    }
+    #[test]
+    fn method_limit_without_app_limit() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Method-Rate-Limit", "50:10".parse().unwrap());
+        headers.insert("X-Method-Rate-Limit-Count", "8:10".parse().unwrap());
+        assert_eq!(remaining(&headers), Some(42));
+
+        headers.insert("X-App-Rate-Limit", "20:1".parse().unwrap());
+        headers.insert("X-App-Rate-Limit-Count", "15:1".parse().unwrap());
+        assert_eq!(remaining(&headers), Some(5));
+    }
+    #[tokio::test]
+    async fn blocks_until_retry_after() {
+        let limiter = RateLimiter::default();
+        limiter.block_for(Duration::from_millis(50));
+        limiter.block_for(Duration::from_millis(10));
+
+        let started = Instant::now();
+        limiter.ready().await;
+        assert!(started.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Error, Handle, Result};
+use crate::{Endpoint, Handle, Result};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -18,20 +18,9 @@ impl GetLeagueDetailsRequestBuilder {
         }
     }
     pub async fn send(self) -> Result<Vec<LeagueResponse>> {
-        let res = self.handle.web.execute(self.request).await?;
-
-        match res.status() {
-            reqwest::StatusCode::OK => {}
-            reqwest::StatusCode::NOT_FOUND => return Err(Error::SummonerNotFound)?,
-            reqwest::StatusCode::TOO_MANY_REQUESTS => return Err(Error::TooManyRequests)?,
-            reqwest::StatusCode::UNAUTHORIZED => return Err(Error::Unauthorized)?,
-            reqwest::StatusCode::FORBIDDEN => return Err(Error::Forbidden)?,
-            reqwest::StatusCode::BAD_REQUEST => return Err(Error::BadRequest)?,
-            reqwest::StatusCode::INTERNAL_SERVER_ERROR => return Err(Error::RiotError)?,
-            _ => {}
-        }
-        let summoner: Vec<LeagueResponse> = res.json().await?;
-        Ok(summoner)
+        self.handle
+            .send(self.request, Endpoint::LeagueEntries)
+            .await
     }
 }
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -40,9 +29,18 @@ pub struct LeagueResponse {
     pub queue_type: String,
     pub tier: Option<String>,
     pub rank: Option<String>,
+    /// Missing from the entries fetched by PUUID
+    #[serde(default)]
     pub summoner_id: String,
+    #[serde(default)]
     pub summoner_name: String,
+    #[serde(default)]
+    pub puuid: String,
     pub league_points: Option<i64>,
+    /// Hyper Roll tier (`GRAY`, `GREEN`, `BLUE`, `PURPLE` or `ORANGE`), in place of `tier`
+    pub rated_tier: Option<String>,
+    /// Hyper Roll rating, in place of `league_points`
+    pub rated_rating: Option<i64>,
     pub wins: i64,
     pub losses: i64,
     pub hot_streak: Option<bool>,
@@ -1,4 +1,4 @@
-use crate::{Error, Handle, Result};
+use crate::{Endpoint, Handle, Result};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -18,20 +18,7 @@ impl GetByPuuidRequestBuilder {
         }
     }
     pub async fn send(self) -> Result<SummonerResponse> {
-        let res = self.handle.web.execute(self.request).await?;
-
-        match res.status() {
-            reqwest::StatusCode::OK => {}
-            reqwest::StatusCode::NOT_FOUND => return Err(Error::SummonerNotFound)?,
-            reqwest::StatusCode::TOO_MANY_REQUESTS => return Err(Error::TooManyRequests)?,
-            reqwest::StatusCode::UNAUTHORIZED => return Err(Error::Unauthorized)?,
-            reqwest::StatusCode::FORBIDDEN => return Err(Error::Forbidden)?,
-            reqwest::StatusCode::BAD_REQUEST => return Err(Error::BadRequest)?,
-            reqwest::StatusCode::INTERNAL_SERVER_ERROR => return Err(Error::RiotError)?,
-            _ => {}
-        }
-        let summoner: SummonerResponse = res.json().await?;
-        Ok(summoner)
+        self.handle.send(self.request, Endpoint::Summoner).await
     }
 }
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]